@@ -132,6 +132,7 @@ fn main() {
                     delivery_tag: 0,
                     exchange: "".into(),
                     routing_key: "unroutable-routing-key-for-tests".into(),
+                    queue: "".into(),
                     redelivered: false,
                     properties: BasicProperties::default().with_priority(42),
                     data: payload.to_vec(),
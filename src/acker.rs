@@ -20,6 +20,7 @@ pub struct Acker {
     internal_rpc: Option<InternalRPCHandle>,
     error: Option<ErrorHolder>,
     used: Arc<AtomicBool>,
+    bound: bool,
 }
 
 impl Acker {
@@ -35,6 +36,15 @@ impl Acker {
             internal_rpc,
             error,
             used: Arc::default(),
+            bound: true,
+        }
+    }
+
+    // Returned messages were never delivered to us, there is nothing to settle
+    pub(crate) fn unbound() -> Self {
+        Self {
+            bound: false,
+            ..Self::new(0, 0, None, None)
         }
     }
 
@@ -77,7 +87,21 @@ impl Acker {
         .await
     }
 
+    /// Reject the message without requeueing it, so that it gets dead-lettered if its queue
+    /// has a dead letter exchange, or discarded otherwise.
+    pub async fn dead_letter(&self) -> Result<()> {
+        self.reject(BasicRejectOptions { requeue: false }).await
+    }
+
+    /// Reject the message and put it back in its queue to have it delivered again.
+    pub async fn requeue(&self) -> Result<()> {
+        self.reject(BasicRejectOptions { requeue: true }).await
+    }
+
     async fn rpc<F: Fn(&InternalRPCHandle, PromiseResolver<()>)>(&self, f: F) -> Result<()> {
+        if !self.bound {
+            return Err(Error::UnboundAcker);
+        }
         if self.used.swap(true, Ordering::SeqCst) {
             return Err(Error::ProtocolError(AMQPError::new(
                 AMQPSoftError::PRECONDITIONFAILED.into(),
@@ -1,10 +1,10 @@
 use crate::{
     id_sequence::IdSequence,
     protocol::{AMQPError, AMQPSoftError},
-    publisher_confirm::{Confirmation, PublisherConfirm},
+    publisher_confirm::{ConfirmStats, Confirmation, PublishRequest, PublisherConfirm},
     returned_messages::ReturnedMessages,
     types::DeliveryTag,
-    Error, Promise,
+    Error, Promise, Result,
 };
 use parking_lot::Mutex;
 use std::{
@@ -28,8 +28,59 @@ impl Acknowledgements {
         ))))
     }
 
-    pub(crate) fn register_pending(&self) -> PublisherConfirm {
-        self.0.lock().register_pending()
+    // The server numbers the publishes in the order it receives them, so the frames are sent
+    // while holding the lock which gives out the delivery tags
+    pub(crate) fn register_pending<R: FnOnce() -> PublishRequest, T, S: FnOnce() -> T>(
+        &self,
+        request: R,
+        send: S,
+    ) -> Result<(PublisherConfirm, T)> {
+        let mut inner = self.0.lock();
+        let confirm = inner.register_pending(request)?;
+        Ok((confirm, send()))
+    }
+
+    pub(crate) fn enable_auto_resend(&self, max_retries: usize) {
+        self.0.lock().max_retries = Some(max_retries);
+    }
+
+    pub(crate) fn resend_nacked<T, S: FnMut(&PublishRequest) -> T>(&self, mut send: S) -> Vec<T> {
+        let mut inner = self.0.lock();
+        let mut sent = Vec::new();
+        while inner.window_has_room(0) && !inner.resends.is_empty() {
+            let resend = inner.resends.remove(0);
+            sent.push(send(&resend.request));
+            inner.register_resend(resend);
+        }
+        sent
+    }
+
+    pub(crate) fn take_failed(&self) -> Vec<PublishRequest> {
+        std::mem::take(&mut self.0.lock().failed)
+    }
+
+    pub(crate) fn pending_count(&self) -> usize {
+        self.0.lock().pending.len()
+    }
+
+    pub(crate) fn set_max_unconfirmed(&self, max_unconfirmed: usize) {
+        self.0.lock().max_unconfirmed = Some(max_unconfirmed);
+    }
+
+    pub(crate) fn stats(&self) -> ConfirmStats {
+        self.0.lock().stats
+    }
+
+    pub(crate) fn next_delivery_tag(&self) -> DeliveryTag {
+        self.0.lock().delivery_tag.peek()
+    }
+
+    pub(crate) fn record_published(&self) {
+        self.0.lock().stats.published += 1;
+    }
+
+    pub(crate) fn record_returned(&self) {
+        self.0.lock().stats.returned += 1;
     }
 
     pub(crate) fn get_last_pending(&self) -> Option<Promise<()>> {
@@ -72,18 +123,35 @@ impl fmt::Debug for Acknowledgements {
             debug
                 .field("delivery_tag", &inner.delivery_tag)
                 .field("returned_messages", &inner.returned_messages)
-                .field("pending", &inner.pending.keys());
+                .field("pending", &inner.pending.keys())
+                .field("max_retries", &inner.max_retries)
+                .field("max_unconfirmed", &inner.max_unconfirmed)
+                .field("failed", &inner.failed.len())
+                .field("stats", &inner.stats);
         }
         debug.finish()
     }
 }
 
+// A nacked publish which still has retries left, waiting to be published again
+struct PendingResend {
+    request: PublishRequest,
+    retries: usize,
+    broadcaster: ConfirmationBroadcaster,
+}
+
 struct Inner {
     channel_id: u16,
     delivery_tag: IdSequence<DeliveryTag>,
     last: Option<(DeliveryTag, Promise<()>)>,
     pending: HashMap<DeliveryTag, ConfirmationBroadcaster>,
     returned_messages: ReturnedMessages,
+    max_retries: Option<usize>,
+    max_unconfirmed: Option<usize>,
+    requests: HashMap<DeliveryTag, (PublishRequest, usize)>,
+    resends: Vec<PendingResend>,
+    failed: Vec<PublishRequest>,
+    stats: ConfirmStats,
 }
 
 impl Inner {
@@ -94,14 +162,51 @@ impl Inner {
             last: None,
             pending: HashMap::default(),
             returned_messages,
+            max_retries: None,
+            max_unconfirmed: None,
+            requests: HashMap::default(),
+            resends: Vec::default(),
+            failed: Vec::default(),
+            stats: ConfirmStats::default(),
         }
     }
 
-    fn register_pending(&mut self) -> PublisherConfirm {
+    // The nacked publishes waiting to be resent keep their place in the window
+    fn window_has_room(&self, waiting_resends: usize) -> bool {
+        self.max_unconfirmed
+            .map_or(true, |max| self.pending.len() + waiting_resends < max)
+    }
+
+    fn register_pending<R: FnOnce() -> PublishRequest>(
+        &mut self,
+        request: R,
+    ) -> Result<PublisherConfirm> {
+        if !self.window_has_room(self.resends.len()) {
+            return Err(Error::ConfirmWindowFull(
+                self.max_unconfirmed.unwrap_or_default(),
+            ));
+        }
+        let (promise, broadcaster) = ConfirmationBroadcaster::new();
+        let request = self.max_retries.map(|_| (request(), 0));
+        let delivery_tag = self.register(broadcaster, request);
+        Ok(PublisherConfirm::new(
+            delivery_tag,
+            promise,
+            self.returned_messages.clone(),
+        ))
+    }
+
+    fn register_resend(&mut self, resend: PendingResend) {
+        self.register(resend.broadcaster, Some((resend.request, resend.retries)));
+    }
+
+    fn register(
+        &mut self,
+        broadcaster: ConfirmationBroadcaster,
+        request: Option<(PublishRequest, usize)>,
+    ) -> DeliveryTag {
         let delivery_tag = self.delivery_tag.next();
         trace!("Publishing with delivery_tag {}", delivery_tag);
-        let (promise, broadcaster) = ConfirmationBroadcaster::new();
-        let promise = PublisherConfirm::new(promise, self.returned_messages.clone());
         if let Some((delivery_tag, promise)) = self.last.take() {
             if let Some(broadcaster) = self.pending.get(&delivery_tag) {
                 broadcaster.unsubscribe(promise);
@@ -109,10 +214,38 @@ impl Inner {
         }
         self.last = Some((delivery_tag, broadcaster.subscribe()));
         self.pending.insert(delivery_tag, broadcaster);
-        promise
+        if let Some(request) = request {
+            self.requests.insert(delivery_tag, request);
+        }
+        delivery_tag
     }
 
-    fn complete_pending(&mut self, success: bool, resolver: ConfirmationBroadcaster) {
+    fn complete_pending(
+        &mut self,
+        delivery_tag: DeliveryTag,
+        success: bool,
+        resolver: ConfirmationBroadcaster,
+    ) {
+        if success {
+            self.stats.confirmed += 1;
+        } else {
+            self.stats.nacked += 1;
+        }
+        if let Some((request, retries)) = self.requests.remove(&delivery_tag) {
+            if !success {
+                if self.max_retries.is_some_and(|max| retries < max) {
+                    trace!(delivery_tag, retries, "Publish nacked, resending it");
+                    self.resends.push(PendingResend {
+                        request,
+                        retries: retries + 1,
+                        broadcaster: resolver,
+                    });
+                    return;
+                }
+                trace!(delivery_tag, retries, "Publish nacked, giving up");
+                self.failed.push(request);
+            }
+        }
         let returned_message = self.returned_messages.get_waiting_message().map(Box::new);
         resolver.swear(Ok(if success {
             Confirmation::Ack(returned_message)
@@ -122,19 +255,14 @@ impl Inner {
     }
 
     fn drop_all(&mut self, success: bool) {
-        for resolver in self
-            .pending
-            .drain()
-            .map(|(_, resolver)| resolver)
-            .collect::<Vec<_>>()
-        {
-            self.complete_pending(success, resolver);
+        for (delivery_tag, resolver) in self.pending.drain().collect::<Vec<_>>() {
+            self.complete_pending(delivery_tag, success, resolver);
         }
     }
 
     fn drop_pending(&mut self, delivery_tag: DeliveryTag, success: bool) -> AMQPResult {
         if let Some(resolver) = self.pending.remove(&delivery_tag) {
-            self.complete_pending(success, resolver);
+            self.complete_pending(delivery_tag, success, resolver);
             Ok(())
         } else {
             Err(AMQPError::new(
@@ -169,8 +297,538 @@ impl Inner {
     }
 
     fn on_channel_error(&mut self, error: Error) {
+        self.requests.clear();
+        self.stats = ConfirmStats::default();
         for (_, resolver) in self.pending.drain() {
             resolver.swear(Err(error.clone()));
         }
+        for resend in self.resends.drain(..) {
+            resend.broadcaster.swear(Err(error.clone()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::connection::test_utils::*;
+
+    use crate::BasicProperties;
+    use amq_protocol::frame::{AMQPContentHeader, AMQPFrame};
+    use amq_protocol::protocol::{basic, AMQPClass};
+    use futures_lite::future::{block_on, poll_once};
+
+    #[test]
+    fn each_confirm_resolves_with_its_delivery_tag() {
+        let acknowledgements = Acknowledgements::new(1, ReturnedMessages::default());
+        let mut confirms = (1..=3)
+            .map(|_| {
+                acknowledgements
+                    .register_pending(|| unreachable!(), || ())
+                    .unwrap()
+                    .0
+            })
+            .collect::<Vec<_>>();
+
+        // Confirmed before being awaited
+        assert!(acknowledgements.ack(1).is_ok());
+        assert!(block_on(&mut confirms[0]).unwrap().is_ack());
+
+        // Confirmed while being awaited
+        assert!(block_on(poll_once(&mut confirms[1])).is_none());
+        assert!(acknowledgements.nack(2).is_ok());
+        assert!(block_on(&mut confirms[1]).unwrap().is_nack());
+
+        // Channel closed before the confirmation
+        let error = Error::InvalidChannelState(crate::ChannelState::Closed);
+        acknowledgements.on_channel_error(error.clone());
+        assert_eq!(block_on(&mut confirms[2]), Err(error));
+    }
+
+    #[test]
+    fn multiple_nack_resolves_as_nack() {
+        let acknowledgements = Acknowledgements::new(1, ReturnedMessages::default());
+        let mut confirms = (1..=6)
+            .map(|_| {
+                acknowledgements
+                    .register_pending(|| unreachable!(), || ())
+                    .unwrap()
+                    .0
+            })
+            .collect::<Vec<_>>();
+
+        assert!(acknowledgements.nack_all_before(5).is_ok());
+
+        for confirm in &mut confirms[..5] {
+            match block_on(poll_once(confirm)) {
+                Some(Ok(confirmation)) => assert!(confirmation.is_nack()),
+                res => panic!("unexpected result: {:?}", res),
+            }
+        }
+        assert!(block_on(poll_once(&mut confirms[5])).is_none());
+    }
+
+    #[test]
+    fn multiple_ack_after_out_of_order_single_acks() {
+        let acknowledgements = Acknowledgements::new(1, ReturnedMessages::default());
+        let mut confirms = (1..=6)
+            .map(|_| {
+                acknowledgements
+                    .register_pending(|| unreachable!(), || ())
+                    .unwrap()
+                    .0
+            })
+            .collect::<Vec<_>>();
+
+        assert!(acknowledgements.ack(4).is_ok());
+        assert!(acknowledgements.nack(2).is_ok());
+        // Covers 1 and 3, 2 and 4 were already confirmed
+        assert!(acknowledgements.ack_all_before(4).is_ok());
+        assert_eq!(acknowledgements.pending_count(), 2);
+        // The multiple ack must stop at its own delivery tag
+        assert!(block_on(poll_once(&mut confirms[4])).is_none());
+        assert!(acknowledgements.ack_all_before(6).is_ok());
+        assert_eq!(acknowledgements.pending_count(), 0);
+
+        for (idx, confirm) in confirms.iter_mut().enumerate() {
+            match block_on(poll_once(confirm)) {
+                Some(Ok(confirmation)) => assert_eq!(confirmation.is_nack(), idx == 1),
+                res => panic!("unexpected result for {}: {:?}", idx + 1, res),
+            }
+        }
+        let stats = acknowledgements.stats();
+        assert_eq!((stats.confirmed, stats.nacked), (5, 1));
+
+        // Everything was already confirmed
+        assert!(acknowledgements.ack(3).is_err());
+        assert!(acknowledgements.ack_all_before(6).is_ok());
+        assert_eq!(acknowledgements.stats(), stats);
+    }
+
+    #[test]
+    fn channel_error_fails_pending_resends() {
+        let acknowledgements = Acknowledgements::new(1, ReturnedMessages::default());
+        acknowledgements.enable_auto_resend(1);
+        let request = || PublishRequest {
+            exchange: "".into(),
+            routing_key: "orders".into(),
+            options: Default::default(),
+            payload: b"order".to_vec(),
+            properties: Default::default(),
+        };
+        let (mut first, ()) = acknowledgements.register_pending(request, || ()).unwrap();
+        let (mut second, ()) = acknowledgements.register_pending(request, || ()).unwrap();
+
+        // The first one gets resent right away, the second one waits for its resend
+        assert!(acknowledgements.nack(1).is_ok());
+        let resent = acknowledgements.resend_nacked(|request| request.routing_key.clone());
+        assert_eq!(resent, vec!["orders".into()]);
+        assert_eq!(acknowledgements.next_delivery_tag(), 4);
+        assert!(acknowledgements.nack(2).is_ok());
+        assert!(block_on(poll_once(&mut second)).is_none());
+
+        let error = Error::InvalidChannelState(crate::ChannelState::Closed);
+        acknowledgements.on_channel_error(error.clone());
+        assert_eq!(block_on(&mut first), Err(error.clone()));
+        assert_eq!(block_on(&mut second), Err(error));
+        assert!(acknowledgements.resend_nacked(|_| ()).is_empty());
+    }
+
+    #[test]
+    fn confirm_window() {
+        let acknowledgements = Acknowledgements::new(1, ReturnedMessages::default());
+        acknowledgements.set_max_unconfirmed(2);
+        acknowledgements.enable_auto_resend(1);
+        let request = || PublishRequest {
+            exchange: "".into(),
+            routing_key: "orders".into(),
+            options: Default::default(),
+            payload: b"order".to_vec(),
+            properties: Default::default(),
+        };
+        for _ in 0..2 {
+            assert!(acknowledgements.register_pending(request, || ()).is_ok());
+        }
+        assert_eq!(
+            acknowledgements
+                .register_pending(request, || panic!("sent over the window"))
+                .err(),
+            Some(Error::ConfirmWindowFull(2))
+        );
+
+        // The nacked publish keeps its place until it gets resent
+        assert!(acknowledgements.nack(1).is_ok());
+        assert!(acknowledgements.register_pending(request, || ()).is_err());
+        assert_eq!(acknowledgements.resend_nacked(|_| ()).len(), 1);
+        assert!(acknowledgements.register_pending(request, || ()).is_err());
+        assert!(acknowledgements.ack(2).is_ok());
+        assert!(acknowledgements.register_pending(request, || ()).is_ok());
+        assert_eq!(acknowledgements.pending_count(), 2);
+    }
+
+    #[test]
+    fn await_publisher_confirm() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        use crate::options::{BasicPublishOptions, ConfirmSelectOptions};
+        use crate::protocol::confirm;
+        use crate::publisher_confirm::Confirmation;
+        use std::cell::Cell;
+
+        let (conn, frames) = running_connection();
+        conn.configuration().set_frame_max(0);
+        let published = Cell::new(0);
+        let mut server = |frame: &AMQPFrame| match frame {
+            AMQPFrame::Method(id, AMQPClass::Confirm(confirm::AMQPMethod::Select(_))) => {
+                vec![AMQPFrame::Method(
+                    *id,
+                    AMQPClass::Confirm(confirm::AMQPMethod::SelectOk(confirm::SelectOk {})),
+                )]
+            }
+            AMQPFrame::Body(id, _) => {
+                published.set(published.get() + 1);
+                vec![AMQPFrame::Method(
+                    *id,
+                    AMQPClass::Basic(basic::AMQPMethod::Ack(basic::Ack {
+                        delivery_tag: published.get(),
+                        multiple: false,
+                    })),
+                )]
+            }
+            frame => passive_declare_reply(frame, |_| None).into_iter().collect(),
+        };
+        let channel =
+            run_against_server(&conn, &frames, conn.create_channel(), &mut server).unwrap();
+        let publish = || {
+            channel.basic_publish(
+                "",
+                "queue",
+                BasicPublishOptions::default(),
+                b"payload",
+                BasicProperties::default(),
+            )
+        };
+
+        // Without confirm mode, there is nothing to wait for
+        let confirm = run_against_server(&conn, &frames, publish(), &mut server).unwrap();
+        assert_eq!(confirm.delivery_tag(), None);
+        assert_eq!(
+            run_against_server(&conn, &frames, confirm, &mut server),
+            Ok(Confirmation::NotRequested)
+        );
+
+        run_against_server(
+            &conn,
+            &frames,
+            channel.confirm_select(ConfirmSelectOptions::default()),
+            &mut server,
+        )
+        .unwrap();
+        published.set(0);
+        for delivery_tag in 1..=2 {
+            let confirm = run_against_server(&conn, &frames, publish(), &mut server).unwrap();
+            assert_eq!(confirm.delivery_tag(), Some(delivery_tag));
+            assert_eq!(
+                run_against_server(&conn, &frames, confirm, &mut server),
+                Ok(Confirmation::Ack(None))
+            );
+        }
+    }
+
+    #[test]
+    fn auto_resend_nacked_publishes() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        use crate::options::{BasicPublishOptions, ConfirmSelectOptions};
+        use crate::protocol::confirm;
+        use std::cell::Cell;
+
+        let (conn, frames) = running_connection();
+        conn.configuration().set_frame_max(0);
+        let nack_all = Cell::new(false);
+        let mut publishes = Vec::new();
+        let mut server = |frame: &AMQPFrame| match frame {
+            AMQPFrame::Method(id, AMQPClass::Confirm(confirm::AMQPMethod::Select(_))) => {
+                vec![AMQPFrame::Method(
+                    *id,
+                    AMQPClass::Confirm(confirm::AMQPMethod::SelectOk(confirm::SelectOk {})),
+                )]
+            }
+            AMQPFrame::Body(id, payload) => {
+                publishes.push(payload.clone());
+                let delivery_tag = publishes.len() as u64;
+                vec![AMQPFrame::Method(
+                    *id,
+                    AMQPClass::Basic(if nack_all.get() || delivery_tag == 1 {
+                        basic::AMQPMethod::Nack(basic::Nack {
+                            delivery_tag,
+                            multiple: false,
+                            requeue: false,
+                        })
+                    } else {
+                        basic::AMQPMethod::Ack(basic::Ack {
+                            delivery_tag,
+                            multiple: false,
+                        })
+                    }),
+                )]
+            }
+            frame => passive_declare_reply(frame, |_| None).into_iter().collect(),
+        };
+
+        let channel =
+            run_against_server(&conn, &frames, conn.create_channel(), &mut server).unwrap();
+        run_against_server(
+            &conn,
+            &frames,
+            channel.confirm_select(ConfirmSelectOptions::default()),
+            &mut server,
+        )
+        .unwrap();
+        assert_eq!(
+            conn.enable_auto_resend(42, 1),
+            Err(Error::InvalidChannel(42))
+        );
+        conn.enable_auto_resend(channel.id(), 1).unwrap();
+        let publish = |payload: &'static [u8]| {
+            let channel = channel.clone();
+            async move {
+                channel
+                    .basic_publish(
+                        "",
+                        "queue",
+                        BasicPublishOptions::default(),
+                        payload,
+                        BasicProperties::default(),
+                    )
+                    .await?
+                    .await
+            }
+        };
+
+        // Nacked once, then acked after being resent
+        let confirmation = run_against_server(&conn, &frames, publish(b"first"), &mut server);
+        assert!(confirmation.unwrap().is_ack());
+        assert!(channel.take_failed_publishes().is_empty());
+
+        // Still nacked after being resent, we give up
+        nack_all.set(true);
+        let confirmation = run_against_server(&conn, &frames, publish(b"second"), &mut server);
+        assert!(confirmation.unwrap().is_nack());
+        assert_eq!(
+            publishes,
+            vec![
+                b"first".to_vec(),
+                b"first".to_vec(),
+                b"second".to_vec(),
+                b"second".to_vec()
+            ]
+        );
+        let failed = conn.take_failed_publishes(channel.id()).unwrap();
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].routing_key.as_str(), "queue");
+        assert_eq!(failed[0].payload, b"second");
+        assert!(conn.take_failed_publishes(channel.id()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn next_publish_seqno() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        use crate::options::{BasicPublishOptions, ConfirmSelectOptions};
+        use crate::protocol::confirm;
+
+        let (conn, frames) = running_connection();
+        conn.configuration().set_frame_max(0);
+        let mut server = |frame: &AMQPFrame| {
+            passive_declare_reply(frame, |method| match method {
+                AMQPClass::Confirm(confirm::AMQPMethod::Select(_)) => Some(AMQPClass::Confirm(
+                    confirm::AMQPMethod::SelectOk(confirm::SelectOk {}),
+                )),
+                _ => None,
+            })
+        };
+
+        let channel =
+            run_against_server(&conn, &frames, conn.create_channel(), &mut server).unwrap();
+        assert_eq!(conn.next_publish_seqno(channel.id()).unwrap(), 0);
+        run_against_server(
+            &conn,
+            &frames,
+            channel.confirm_select(ConfirmSelectOptions::default()),
+            &mut server,
+        )
+        .unwrap();
+        for expected in 1..=3 {
+            let seqno = conn.next_publish_seqno(channel.id()).unwrap();
+            assert_eq!(seqno, expected);
+            let confirm = run_against_server(
+                &conn,
+                &frames,
+                channel.basic_publish(
+                    "",
+                    "queue",
+                    BasicPublishOptions::default(),
+                    b"payload",
+                    BasicProperties::default(),
+                ),
+                &mut server,
+            )
+            .unwrap();
+            assert_eq!(confirm.delivery_tag(), Some(seqno));
+        }
+        assert_eq!(conn.next_publish_seqno(channel.id()).unwrap(), 4);
+        assert_eq!(conn.next_publish_seqno(42), Err(Error::InvalidChannel(42)));
+    }
+
+    #[test]
+    fn channel_confirm_stats() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        use crate::options::{BasicPublishOptions, ConfirmSelectOptions};
+        use crate::protocol::confirm;
+
+        let (conn, frames) = running_connection();
+        conn.configuration().set_frame_max(0);
+        let mut server = |frame: &AMQPFrame| {
+            passive_declare_reply(frame, |method| match method {
+                AMQPClass::Confirm(confirm::AMQPMethod::Select(_)) => Some(AMQPClass::Confirm(
+                    confirm::AMQPMethod::SelectOk(confirm::SelectOk {}),
+                )),
+                _ => None,
+            })
+        };
+
+        let channel =
+            run_against_server(&conn, &frames, conn.create_channel(), &mut server).unwrap();
+        run_against_server(
+            &conn,
+            &frames,
+            channel.confirm_select(ConfirmSelectOptions::default()),
+            &mut server,
+        )
+        .unwrap();
+        for _ in 1..=3 {
+            run_against_server(
+                &conn,
+                &frames,
+                channel.basic_publish(
+                    "",
+                    "queue",
+                    BasicPublishOptions::default(),
+                    b"payload",
+                    BasicProperties::default(),
+                ),
+                &mut server,
+            )
+            .unwrap();
+        }
+        assert_eq!(
+            conn.channel_confirm_stats(channel.id()).unwrap(),
+            ConfirmStats {
+                published: 3,
+                ..Default::default()
+            }
+        );
+
+        for frame in [
+            AMQPFrame::Method(
+                channel.id(),
+                AMQPClass::Basic(basic::AMQPMethod::Ack(basic::Ack {
+                    delivery_tag: 2,
+                    multiple: true,
+                })),
+            ),
+            AMQPFrame::Method(
+                channel.id(),
+                AMQPClass::Basic(basic::AMQPMethod::Return(basic::Return {
+                    reply_code: 312,
+                    reply_text: "NO_ROUTE".into(),
+                    exchange: "".into(),
+                    routing_key: "queue".into(),
+                })),
+            ),
+            AMQPFrame::Header(
+                channel.id(),
+                60,
+                Box::new(AMQPContentHeader {
+                    class_id: 60,
+                    body_size: 7,
+                    properties: BasicProperties::default(),
+                }),
+            ),
+            AMQPFrame::Body(channel.id(), b"payload".to_vec()),
+            AMQPFrame::Method(
+                channel.id(),
+                AMQPClass::Basic(basic::AMQPMethod::Nack(basic::Nack {
+                    delivery_tag: 3,
+                    multiple: false,
+                    requeue: false,
+                })),
+            ),
+        ] {
+            handle_frame(&conn, frame).unwrap();
+        }
+        assert_eq!(
+            conn.channel_confirm_stats(channel.id()).unwrap(),
+            ConfirmStats {
+                published: 3,
+                confirmed: 2,
+                nacked: 1,
+                returned: 1,
+            }
+        );
+        assert_eq!(
+            conn.channel_confirm_stats(42),
+            Err(Error::InvalidChannel(42))
+        );
+
+        run_against_server(&conn, &frames, channel.close(200, "OK"), &mut server).unwrap();
+        assert_eq!(channel.confirm_stats(), ConfirmStats::default());
+    }
+
+    #[test]
+    fn max_unconfirmed_publishes() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        use crate::options::BasicPublishOptions;
+        use futures_lite::future::{block_on, poll_once};
+
+        let (conn, channel, frames) = connected_channel_with_frames();
+        conn.configuration().set_frame_max(4096);
+        channel.status().set_confirm();
+        conn.set_max_unconfirmed(channel.id(), 2).unwrap();
+        let publish = || {
+            let res = block_on(poll_once(channel.basic_publish(
+                "",
+                "queue",
+                BasicPublishOptions::default(),
+                b"payload",
+                BasicProperties::default(),
+            )));
+            while let Some((_, resolver)) = frames.pop(true) {
+                if let Some(resolver) = resolver {
+                    resolver.swear(Ok(()));
+                }
+            }
+            res
+        };
+
+        assert!(publish().is_none());
+        assert!(publish().is_none());
+        assert!(matches!(publish(), Some(Err(Error::ConfirmWindowFull(2)))));
+        handle_frame(
+            &conn,
+            AMQPFrame::Method(
+                channel.id(),
+                AMQPClass::Basic(basic::AMQPMethod::Ack(basic::Ack {
+                    delivery_tag: 1,
+                    multiple: false,
+                })),
+            ),
+        )
+        .unwrap();
+        assert!(publish().is_none());
+        assert!(matches!(publish(), Some(Err(Error::ConfirmWindowFull(2)))));
     }
 }
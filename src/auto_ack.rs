@@ -0,0 +1,189 @@
+use crate::{
+    consumer::ConsumerDelegate,
+    message::{Delivery, DeliveryResult},
+    options::BasicAckOptions,
+};
+use std::{fmt, future::Future, pin::Pin, sync::Arc};
+use tracing::{error, trace, warn};
+
+/// What [`AutoAck`] did with a delivery once its handler returned.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Settlement {
+    /// The handler succeeded, the delivery got acked.
+    Ack,
+    /// The handler failed, the delivery got put back in its queue to be retried.
+    Requeue,
+    /// The handler failed too many times, the delivery got rejected without being requeued so
+    /// that it gets dead-lettered.
+    DeadLetter,
+}
+
+/// A [`ConsumerDelegate`] acking the deliveries once its handler successfully processed them.
+///
+/// When the handler fails, the delivery gets requeued to be retried once, and is dead-lettered if
+/// it fails again. The retry is detected from the `redelivered` flag, as requeueing a delivery
+/// doesn't add anything else to it: a delivery which was redelivered for another reason, such as
+/// a channel closed before it got acked, is dead-lettered on its first failure.
+///
+/// The handler runs on the executor, not on the thread reading the frames from the server.
+///
+/// ```rust,no_run
+/// use lapin::{AutoAck, Consumer};
+///
+/// fn process(consumer: &Consumer) {
+///     consumer.set_delegate(AutoAck::new(|delivery| {
+///         std::str::from_utf8(&delivery.data).map(|data| println!("{}", data))
+///     }));
+/// }
+/// ```
+///
+/// [`ConsumerDelegate`]: ./trait.ConsumerDelegate.html
+pub struct AutoAck<H> {
+    handler: Arc<H>,
+}
+
+impl<E: fmt::Display, H: Fn(Delivery) -> Result<(), E> + Send + Sync + 'static> AutoAck<H> {
+    pub fn new(handler: H) -> Self {
+        Self {
+            handler: Arc::new(handler),
+        }
+    }
+
+    async fn process(handler: Arc<H>, delivery: Delivery) -> Settlement {
+        let acker = delivery.acker.clone();
+        let delivery_tag = delivery.delivery_tag;
+        let redelivered = delivery.redelivered;
+        let settlement = match handler(delivery) {
+            Ok(()) => Settlement::Ack,
+            Err(err) if !redelivered => {
+                warn!(%err, delivery_tag, "Failed to handle delivery, requeueing it");
+                Settlement::Requeue
+            }
+            Err(err) => {
+                warn!(%err, delivery_tag, "Failed to handle redelivery, dead-lettering it");
+                Settlement::DeadLetter
+            }
+        };
+        let res = match settlement {
+            Settlement::Ack => acker.ack(BasicAckOptions::default()).await,
+            Settlement::Requeue => acker.requeue().await,
+            Settlement::DeadLetter => acker.dead_letter().await,
+        };
+        if let Err(err) = res {
+            error!(%err, delivery_tag, ?settlement, "Failed to settle delivery");
+        }
+        settlement
+    }
+}
+
+impl<E: fmt::Display, H: Fn(Delivery) -> Result<(), E> + Send + Sync + 'static> ConsumerDelegate
+    for AutoAck<H>
+{
+    fn on_new_delivery(
+        &self,
+        delivery: DeliveryResult,
+    ) -> Pin<Box<dyn Future<Output = ()> + Send>> {
+        match delivery {
+            Ok(Some(delivery)) => {
+                // We're called with the consumer locked, only run the handler once spawned
+                let handler = self.handler.clone();
+                Box::pin(async move {
+                    Self::process(handler, delivery).await;
+                })
+            }
+            Ok(None) => {
+                trace!("Consumer canceled");
+                Box::pin(async {})
+            }
+            Err(err) => {
+                error!(%err, "Consumer failed");
+                Box::pin(async {})
+            }
+        }
+    }
+}
+
+impl<H> fmt::Debug for AutoAck<H> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AutoAck").finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        options::BasicConsumeOptions,
+        types::{FieldTable, ShortString},
+        BasicProperties, Consumer,
+    };
+    use futures_lite::future::block_on;
+    use std::{sync::mpsc, time::Duration};
+
+    fn delivery(redelivered: bool) -> Delivery {
+        let mut delivery = Delivery::new(1, 1, "".into(), "jobs".into(), redelivered, None, None);
+        delivery.data = b"payload".to_vec();
+        delivery
+    }
+
+    fn settle<H: Fn(Delivery) -> Result<(), String> + Send + Sync + 'static>(
+        auto_ack: &AutoAck<H>,
+        delivery: Delivery,
+    ) -> Settlement {
+        let acker = delivery.acker.clone();
+        let settlement = block_on(AutoAck::process(auto_ack.handler.clone(), delivery));
+        assert!(acker.used());
+        settlement
+    }
+
+    #[test]
+    fn ack_on_success() {
+        let auto_ack = AutoAck::new(|delivery: Delivery| {
+            assert_eq!(delivery.data, b"payload");
+            Ok::<(), String>(())
+        });
+        assert_eq!(settle(&auto_ack, delivery(false)), Settlement::Ack);
+        assert_eq!(settle(&auto_ack, delivery(true)), Settlement::Ack);
+    }
+
+    #[test]
+    fn requeue_then_dead_letter() {
+        let auto_ack = AutoAck::new(|_| Err("unavailable".to_string()));
+        assert_eq!(settle(&auto_ack, delivery(false)), Settlement::Requeue);
+        assert_eq!(settle(&auto_ack, delivery(true)), Settlement::DeadLetter);
+    }
+
+    #[test]
+    fn delegate_settles_deliveries() {
+        let auto_ack = AutoAck::new(|_| Ok::<(), String>(()));
+        let delivery = delivery(false);
+        let acker = delivery.acker.clone();
+        block_on(auto_ack.on_new_delivery(Ok(Some(delivery))));
+        assert!(acker.used());
+        block_on(auto_ack.on_new_delivery(Ok(None)));
+    }
+
+    #[test]
+    fn handler_uses_its_consumer() {
+        let consumer = Consumer::new(
+            ShortString::from("jobs-consumer"),
+            Arc::new(async_global_executor_trait::AsyncGlobalExecutor),
+            None,
+            "jobs".into(),
+            BasicConsumeOptions::default(),
+            FieldTable::default(),
+        );
+        let (in_flight_in, in_flight_out) = mpsc::channel();
+        let handler_consumer = consumer.clone();
+        consumer.set_delegate(AutoAck::new(move |_| {
+            in_flight_in.send(handler_consumer.in_flight()).unwrap();
+            Ok::<(), String>(())
+        }));
+
+        // The delivery completes with the consumer locked, as it does in the io loop
+        consumer.start_new_delivery(delivery(false));
+        consumer.handle_content_header_frame(7, BasicProperties::default());
+        consumer.handle_body_frame(0, b"payload".to_vec());
+        assert_eq!(in_flight_out.recv_timeout(Duration::from_secs(5)), Ok(1));
+    }
+}
@@ -63,9 +63,10 @@ impl Inner {
         &mut self,
         queue: ShortString,
         options: BasicGetOptions,
-        message: BasicGetMessage,
+        mut message: BasicGetMessage,
         resolver: PromiseResolver<Option<BasicGetMessage>>,
     ) {
+        message.delivery.queue = queue.clone();
         self.0 = Some(InnerData {
             queue,
             options,
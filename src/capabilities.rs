@@ -0,0 +1,237 @@
+use crate::{
+    types::{AMQPValue, FieldTable},
+    Error, Result,
+};
+use parking_lot::RwLock;
+use std::{fmt, sync::Arc};
+
+/// The capabilities advertised by the server in its properties during the connection handshake.
+///
+/// Every getter returns `false` until the server has advertised the corresponding capability.
+#[derive(Clone, Default)]
+pub struct Capabilities {
+    inner: Arc<RwLock<Option<Inner>>>,
+}
+
+struct Inner {
+    // As advertised by the server
+    capabilities: FieldTable,
+    // Inferred from the server product and version, for servers not advertising it
+    immediate: bool,
+}
+
+impl Capabilities {
+    /// Whether channels can be put in confirm mode, see `Channel::confirm_select`.
+    pub fn publisher_confirms(&self) -> bool {
+        self.get("publisher_confirms")
+    }
+
+    /// Whether exchanges can be bound to other exchanges, see `Channel::exchange_bind`.
+    pub fn exchange_exchange_bindings(&self) -> bool {
+        self.get("exchange_exchange_bindings")
+    }
+
+    /// Whether the server accepts `basic.nack`, and nacks publishes in confirm mode.
+    pub fn basic_nack(&self) -> bool {
+        self.get("basic.nack")
+    }
+
+    /// Whether the server cancels our consumers when their queue is deleted.
+    pub fn consumer_cancel_notify(&self) -> bool {
+        self.get("consumer_cancel_notify")
+    }
+
+    /// Whether the server notifies us when it blocks the connection because of resource alarms.
+    pub fn connection_blocked(&self) -> bool {
+        self.get("connection.blocked")
+    }
+
+    /// Whether consumers can be given an `x-priority` argument.
+    pub fn consumer_priorities(&self) -> bool {
+        self.get("consumer_priorities")
+    }
+
+    /// Whether the server reports authentication failures with a connection close.
+    pub fn authentication_failure_close(&self) -> bool {
+        self.get("authentication_failure_close")
+    }
+
+    /// Whether the `global` flag of `basic.qos` has the RabbitMQ per-consumer meaning.
+    pub fn per_consumer_qos(&self) -> bool {
+        self.get("per_consumer_qos")
+    }
+
+    /// Whether the server supports the `amq.rabbitmq.reply-to` pseudo queue.
+    pub fn direct_reply_to(&self) -> bool {
+        self.get("direct_reply_to")
+    }
+
+    /// Whether the server accepts publishes with the `immediate` flag.
+    ///
+    /// Servers usually don't advertise it, in which case it's inferred from their product and
+    /// version: RabbitMQ dropped it in 3.0 and closes the connection with `NOT_IMPLEMENTED` when
+    /// it's used, so we only consider it unsupported there.
+    pub fn immediate(&self) -> bool {
+        self.inner.read().as_ref().is_some_and(|inner| {
+            Self::advertised(&inner.capabilities, "immediate").unwrap_or(inner.immediate)
+        })
+    }
+
+    pub(crate) fn set_server_properties(&self, server_properties: &FieldTable) {
+        let capabilities = match server_properties.inner().get("capabilities") {
+            Some(AMQPValue::FieldTable(capabilities)) => capabilities.clone(),
+            _ => FieldTable::default(),
+        };
+        *self.inner.write() = Some(Inner {
+            capabilities,
+            immediate: !Self::is_rabbitmq_3_or_later(server_properties),
+        });
+    }
+
+    // Only refuse once we know what the server supports, nothing has been advertised before the
+    // connection handshake
+    pub(crate) fn check(&self, capability: &'static str) -> Result<()> {
+        let supported = if capability == "immediate" {
+            self.immediate()
+        } else {
+            self.get(capability)
+        };
+        if self.inner.read().is_some() && !supported {
+            return Err(Error::UnsupportedByServer(capability));
+        }
+        Ok(())
+    }
+
+    fn is_rabbitmq_3_or_later(server_properties: &FieldTable) -> bool {
+        let property = |key| {
+            server_properties
+                .inner()
+                .get(key)
+                .and_then(AMQPValue::as_long_string)
+                .map(ToString::to_string)
+        };
+        property("product").as_deref() == Some("RabbitMQ")
+            && property("version")
+                .and_then(|version| version.split('.').next()?.parse::<u32>().ok())
+                // Assume a recent version if we cannot tell
+                .map_or(true, |major| major >= 3)
+    }
+
+    fn advertised(capabilities: &FieldTable, capability: &str) -> Option<bool> {
+        capabilities
+            .inner()
+            .get(capability)
+            .map(|value| matches!(value, AMQPValue::Boolean(true)))
+    }
+
+    fn get(&self, capability: &str) -> bool {
+        self.inner
+            .read()
+            .as_ref()
+            .and_then(|inner| Self::advertised(&inner.capabilities, capability))
+            .unwrap_or(false)
+    }
+}
+
+impl fmt::Debug for Capabilities {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug = f.debug_tuple("Capabilities");
+        if let Some(inner) = self.inner.try_read() {
+            debug.field(&inner.as_ref().map(|inner| &inner.capabilities));
+        }
+        debug.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_server_properties() {
+        let mut capabilities = FieldTable::default();
+        capabilities.insert("publisher_confirms".into(), true.into());
+        capabilities.insert("exchange_exchange_bindings".into(), false.into());
+        capabilities.insert("basic.nack".into(), true.into());
+        capabilities.insert("consumer_cancel_notify".into(), true.into());
+        capabilities.insert(
+            "direct_reply_to".into(),
+            AMQPValue::LongString("yes".into()),
+        );
+        let mut server_properties = FieldTable::default();
+        server_properties.insert("product".into(), AMQPValue::LongString("RabbitMQ".into()));
+        server_properties.insert("capabilities".into(), AMQPValue::FieldTable(capabilities));
+
+        let capabilities = Capabilities::default();
+        assert!(!capabilities.publisher_confirms());
+        assert_eq!(capabilities.check("exchange_exchange_bindings"), Ok(()));
+
+        capabilities.set_server_properties(&server_properties);
+        assert!(capabilities.publisher_confirms());
+        assert!(capabilities.basic_nack());
+        assert!(capabilities.consumer_cancel_notify());
+        assert!(!capabilities.exchange_exchange_bindings());
+        assert!(!capabilities.direct_reply_to());
+        assert!(!capabilities.per_consumer_qos());
+        assert_eq!(capabilities.check("publisher_confirms"), Ok(()));
+        assert_eq!(
+            capabilities.check("exchange_exchange_bindings"),
+            Err(Error::UnsupportedByServer("exchange_exchange_bindings"))
+        );
+        assert!(!capabilities.immediate());
+    }
+
+    #[test]
+    fn immediate() {
+        let capabilities = Capabilities::default();
+        assert_eq!(capabilities.check("immediate"), Ok(()));
+        for (product, version, immediate) in [
+            ("RabbitMQ", Some("3.13.1"), false),
+            ("RabbitMQ", Some("4.0.2"), false),
+            ("RabbitMQ", None, false),
+            ("RabbitMQ", Some("2.8.7"), true),
+            ("qpid", Some("9.0.0"), true),
+        ] {
+            let mut server_properties = FieldTable::default();
+            server_properties.insert("product".into(), AMQPValue::LongString(product.into()));
+            if let Some(version) = version {
+                server_properties.insert("version".into(), AMQPValue::LongString(version.into()));
+            }
+            capabilities.set_server_properties(&server_properties);
+            assert_eq!(capabilities.immediate(), immediate);
+            assert!(!capabilities
+                .inner
+                .read()
+                .as_ref()
+                .unwrap()
+                .capabilities
+                .inner()
+                .contains_key("immediate"));
+            assert_eq!(
+                capabilities.check("immediate").is_ok(),
+                immediate,
+                "{} {:?}",
+                product,
+                version
+            );
+        }
+    }
+
+    #[test]
+    fn advertised_immediate() {
+        let capabilities = Capabilities::default();
+        for advertised in [true, false] {
+            let mut advertised_capabilities = FieldTable::default();
+            advertised_capabilities.insert("immediate".into(), advertised.into());
+            let mut server_properties = FieldTable::default();
+            server_properties.insert("product".into(), AMQPValue::LongString("RabbitMQ".into()));
+            server_properties.insert(
+                "capabilities".into(),
+                AMQPValue::FieldTable(advertised_capabilities),
+            );
+            capabilities.set_server_properties(&server_properties);
+            assert_eq!(capabilities.immediate(), advertised);
+            assert_eq!(capabilities.check("immediate").is_ok(), advertised);
+        }
+    }
+}
@@ -7,29 +7,39 @@ use crate::{
     channel_status::{ChannelState, ChannelStatus},
     connection_closer::ConnectionCloser,
     connection_status::{ConnectionState, ConnectionStep},
-    consumer::Consumer,
+    consumer::{Consumer, MultiConsumer},
     consumers::Consumers,
+    delivery_tags::DeliveryTags,
     error_handler::ErrorHandler,
+    frame_observer::{Direction, FrameObserver},
     frames::{ExpectedReply, Frames},
     internal_rpc::InternalRPCHandle,
     message::{BasicGetMessage, BasicReturnMessage, Delivery},
-    protocol::{self, AMQPClass, AMQPError, AMQPHardError},
-    publisher_confirm::PublisherConfirm,
-    queue::Queue,
+    properties::PERSISTENT_DELIVERY_MODE,
+    protocol::{self, AMQPClass, AMQPError, AMQPErrorKind, AMQPHardError, AMQPSoftError},
+    publisher_confirm::{ConfirmStats, PublishRequest, PublisherConfirm},
+    queue::{Queue, QueueBind, QueueDeclare},
     registry::Registry,
     returned_messages::ReturnedMessages,
     socket_state::SocketStateHandle,
     topology::RestoredChannel,
     topology_internal::ChannelDefinitionInternal,
     types::*,
-    BasicProperties, Configuration, Connection, ConnectionStatus, Error, ExchangeKind, Promise,
-    PromiseResolver, Result,
+    BasicProperties, Configuration, Connection, ConnectionStatus, Error, ExchangeDeclare,
+    ExchangeKind, Promise, PromiseResolver, Result,
 };
 use amq_protocol::frame::{AMQPContentHeader, AMQPFrame};
 use executor_trait::FullExecutor;
 use serde::{Deserialize, Serialize};
-use std::{convert::TryFrom, fmt, sync::Arc};
-use tracing::{error, info, level_enabled, trace, Level};
+use std::{
+    convert::TryFrom,
+    fmt,
+    future::{poll_fn, Future},
+    sync::Arc,
+    task::Poll,
+    time::Duration,
+};
+use tracing::{error, info, level_enabled, trace, warn, Level};
 
 /// Main entry point for most AMQP operations.
 ///
@@ -50,11 +60,13 @@ pub struct Channel {
     local_registry: Registry,
     acknowledgements: Acknowledgements,
     consumers: Consumers,
+    delivery_tags: DeliveryTags,
     basic_get_delivery: BasicGetDelivery,
     returned_messages: ReturnedMessages,
     waker: SocketStateHandle,
     internal_rpc: InternalRPCHandle,
     frames: Frames,
+    frame_observer: FrameObserver,
     error_handler: ErrorHandler,
     executor: Arc<dyn FullExecutor + Send + Sync>,
     channel_closer: Option<Arc<ChannelCloser>>,
@@ -76,6 +88,7 @@ impl fmt::Debug for Channel {
             .field("connection_status", &self.connection_status)
             .field("acknowledgements", &self.acknowledgements)
             .field("consumers", &self.consumers)
+            .field("delivery_tags", &self.delivery_tags)
             .field("basic_get_delivery", &self.basic_get_delivery)
             .field("returned_messages", &self.returned_messages)
             .field("frames", &self.frames)
@@ -84,6 +97,7 @@ impl fmt::Debug for Channel {
 }
 
 impl Channel {
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn new(
         channel_id: ChannelId,
         configuration: Configuration,
@@ -92,6 +106,7 @@ impl Channel {
         waker: SocketStateHandle,
         internal_rpc: InternalRPCHandle,
         frames: Frames,
+        frame_observer: FrameObserver,
         executor: Arc<dyn FullExecutor + Send + Sync>,
         connection_closer: Option<Arc<ConnectionCloser>>,
     ) -> Channel {
@@ -115,11 +130,13 @@ impl Channel {
             local_registry: Registry::default(),
             acknowledgements: Acknowledgements::new(channel_id, returned_messages.clone()),
             consumers: Consumers::default(),
+            delivery_tags: DeliveryTags::default(),
             basic_get_delivery: BasicGetDelivery::default(),
             returned_messages,
             waker,
             internal_rpc,
             frames,
+            frame_observer,
             error_handler: ErrorHandler::default(),
             executor,
             channel_closer,
@@ -135,8 +152,22 @@ impl Channel {
         self.error_handler.set_handler(handler);
     }
 
+    /// Register a handler called with each message the server returns to us because it
+    /// couldn't be routed while being published with `mandatory`.
+    ///
+    /// Once a handler is registered, the returned messages are handed to it instead of being
+    /// attached to the publisher confirms or returned by [`Channel::wait_for_confirms`].
+    ///
+    /// The handler is called from the io loop: it should hand the messages off to another task
+    /// rather than block, which would stall the whole connection.
+    pub fn on_return<R: FnMut(BasicReturnMessage) + Send + 'static>(&self, handler: R) {
+        self.returned_messages.set_handler(handler);
+    }
+
+    // The server requeues everything we didn't ack when the channel dies, forget about it
     pub(crate) fn reset(&self) {
-        // FIXME
+        self.delivery_tags.clear();
+        self.consumers.reset();
     }
 
     pub(crate) async fn restore(
@@ -224,6 +255,7 @@ impl Channel {
     pub(crate) fn set_connection_error(&self, error: Error) {
         self.set_state(ChannelState::Error);
         self.error_publisher_confirms(error.clone());
+        self.reset();
         self.error_consumers(error.clone());
         self.internal_rpc.remove_channel(self.id, error.clone());
     }
@@ -248,6 +280,14 @@ impl Channel {
         self.id
     }
 
+    /// Get the number of deliveries received on this channel, by its consumers or with
+    /// `basic_get`, which haven't been acked, nacked or rejected yet.
+    ///
+    /// This is the number of [`Channel::unacked_deliveries`].
+    pub fn in_flight(&self) -> usize {
+        self.delivery_tags.len()
+    }
+
     pub(crate) fn clone_internal(&self) -> Self {
         Self {
             id: self.id,
@@ -258,11 +298,13 @@ impl Channel {
             local_registry: self.local_registry.clone(),
             acknowledgements: self.acknowledgements.clone(),
             consumers: self.consumers.clone(),
+            delivery_tags: self.delivery_tags.clone(),
             basic_get_delivery: self.basic_get_delivery.clone(),
             returned_messages: self.returned_messages.clone(),
             waker: self.waker.clone(),
             internal_rpc: self.internal_rpc.clone(),
             frames: self.frames.clone(),
+            frame_observer: self.frame_observer.clone(),
             error_handler: self.error_handler.clone(),
             executor: self.executor.clone(),
             channel_closer: None,
@@ -301,6 +343,13 @@ impl Channel {
         self.do_channel_close(reply_code, reply_text, 0, 0).await
     }
 
+    /// Start a consumer on the given queue.
+    ///
+    /// When `exclusive` is set and the server refuses it because the queue is already being
+    /// consumed, this fails with [`Error::ExclusiveConsumeConflict`].
+    ///
+    /// When `consumer_tag` is already used by another consumer of this channel, this fails with
+    /// [`Error::DuplicateConsumerTag`] without asking the server.
     pub async fn basic_consume(
         &self,
         queue: &str,
@@ -308,8 +357,53 @@ impl Channel {
         options: BasicConsumeOptions,
         arguments: FieldTable,
     ) -> Result<Consumer> {
+        // The server would close the channel with NOT_ALLOWED
+        if !consumer_tag.is_empty() && self.consumers.contains(consumer_tag) {
+            return Err(Error::DuplicateConsumerTag(consumer_tag.into()));
+        }
         self.do_basic_consume(queue, consumer_tag, options, arguments, None)
             .await
+            .map_err(|error| match error {
+                // RabbitMQ refuses the access to the queue when it already has an exclusive
+                // consumer, or when we asked for exclusivity while it already has consumers
+                Error::ProtocolError(error)
+                    if options.exclusive
+                        && error.kind() == &AMQPErrorKind::Soft(AMQPSoftError::ACCESSREFUSED) =>
+                {
+                    Error::ExclusiveConsumeConflict(error)
+                }
+                error => error,
+            })
+    }
+
+    /// Start a consumer with a server generated tag on each of the given queues, and merge their
+    /// deliveries into a single stream.
+    ///
+    /// If one of them fails, the consumers already started are canceled when dropped.
+    pub async fn basic_consume_many(
+        &self,
+        queues: &[ShortString],
+        options: BasicConsumeOptions,
+    ) -> Result<MultiConsumer> {
+        let mut consumers = Vec::with_capacity(queues.len());
+        for queue in queues {
+            consumers.push(
+                self.basic_consume(queue.as_str(), "", options, FieldTable::default())
+                    .await?,
+            );
+        }
+        Ok(MultiConsumer::new(consumers))
+    }
+
+    /// Cancel all the active consumers of this channel.
+    ///
+    /// Consumers which are already being canceled, either by us or by the server, are left
+    /// untouched.
+    pub async fn basic_cancel_all(&self, options: BasicCancelOptions) -> Result<()> {
+        for consumer_tag in self.consumers.active_tags() {
+            self.basic_cancel(consumer_tag.as_str(), options).await?;
+        }
+        Ok(())
     }
 
     pub async fn basic_get(
@@ -320,6 +414,97 @@ impl Channel {
         self.do_basic_get(queue, options, None).await
     }
 
+    /// Get all the messages from a queue, using [`Channel::basic_get`] until the server tells us
+    /// that there are no messages left.
+    ///
+    /// The messages published to the queue in the meantime might not be part of the result.
+    pub async fn basic_get_all(
+        &self,
+        queue: &str,
+        options: BasicGetOptions,
+    ) -> Result<Vec<BasicGetMessage>> {
+        let mut messages = Vec::new();
+        while let Some(message) = self.basic_get(queue, options).await? {
+            let remaining = message.remaining();
+            messages.push(message);
+            if remaining == 0 {
+                break;
+            }
+        }
+        Ok(messages)
+    }
+
+    /// Acknowledge one or several deliveries.
+    ///
+    /// Fails with [`Error::UnknownDeliveryTag`] without contacting the server if the delivery
+    /// tag doesn't match a delivery we still need to acknowledge, as the server would close the
+    /// channel in that case. A `delivery_tag` of 0 with `multiple` acknowledges everything.
+    pub async fn basic_ack(
+        &self,
+        delivery_tag: DeliveryTag,
+        options: BasicAckOptions,
+    ) -> Result<()> {
+        self.delivery_tags.check(delivery_tag, options.multiple)?;
+        self.do_basic_ack(delivery_tag, options).await
+    }
+
+    /// Acknowledge several deliveries, sending as few frames as possible.
+    ///
+    /// The longest run of our oldest deliveries to acknowledge is acknowledged at once with
+    /// `multiple`, the other ones one by one. Returns the number of frames sent.
+    pub async fn basic_ack_many(&self, delivery_tags: &[DeliveryTag]) -> Result<usize> {
+        let (run_end, others) = self.delivery_tags.coalesce(delivery_tags)?;
+        let mut sent = 0;
+        if let Some(delivery_tag) = run_end {
+            self.basic_ack(delivery_tag, BasicAckOptions { multiple: true })
+                .await?;
+            sent += 1;
+        }
+        for delivery_tag in others {
+            self.basic_ack(delivery_tag, BasicAckOptions::default())
+                .await?;
+            sent += 1;
+        }
+        Ok(sent)
+    }
+
+    /// Reject one or several deliveries.
+    ///
+    /// The delivery tag is checked the same way as in [`Channel::basic_ack`].
+    pub async fn basic_nack(
+        &self,
+        delivery_tag: DeliveryTag,
+        options: BasicNackOptions,
+    ) -> Result<()> {
+        self.delivery_tags.check(delivery_tag, options.multiple)?;
+        self.do_basic_nack(delivery_tag, options).await
+    }
+
+    /// Reject a delivery.
+    ///
+    /// The delivery tag is checked the same way as in [`Channel::basic_ack`].
+    pub async fn basic_reject(
+        &self,
+        delivery_tag: DeliveryTag,
+        options: BasicRejectOptions,
+    ) -> Result<()> {
+        self.delivery_tags.check(delivery_tag, false)?;
+        self.do_basic_reject(delivery_tag, options).await
+    }
+
+    /// Reject a delivery without requeueing it, so that it gets dead-lettered if its queue has
+    /// a dead letter exchange, or discarded otherwise.
+    pub async fn basic_dead_letter(&self, delivery_tag: DeliveryTag) -> Result<()> {
+        self.basic_reject(delivery_tag, BasicRejectOptions { requeue: false })
+            .await
+    }
+
+    /// Reject a delivery and put it back in its queue to have it delivered again.
+    pub async fn basic_requeue(&self, delivery_tag: DeliveryTag) -> Result<()> {
+        self.basic_reject(delivery_tag, BasicRejectOptions { requeue: true })
+            .await
+    }
+
     pub async fn exchange_declare(
         &self,
         exchange: &str,
@@ -331,6 +516,249 @@ impl Channel {
             .await
     }
 
+    /// Declare an exchange described by an [`ExchangeDeclare`] builder.
+    ///
+    /// [`ExchangeDeclare`]: ./struct.ExchangeDeclare.html
+    pub async fn declare_exchange(&self, declare: ExchangeDeclare) -> Result<()> {
+        self.exchange_declare(
+            declare.exchange.as_str(),
+            declare.kind,
+            declare.options,
+            declare.arguments,
+        )
+        .await
+    }
+
+    /// Bind an exchange to another exchange.
+    ///
+    /// This is a RabbitMQ extension: once connected, it always fails with
+    /// [`Error::UnsupportedByServer`] without contacting the server if the server didn't
+    /// advertise the `exchange_exchange_bindings` capability, see
+    /// [`Capabilities::exchange_exchange_bindings`]. There is no way to skip this check.
+    ///
+    /// [`Capabilities::exchange_exchange_bindings`]: ./struct.Capabilities.html#method.exchange_exchange_bindings
+    pub async fn exchange_bind(
+        &self,
+        destination: &str,
+        source: &str,
+        routing_key: &str,
+        options: ExchangeBindOptions,
+        arguments: FieldTable,
+    ) -> Result<()> {
+        self.configuration
+            .server_capabilities()
+            .check("exchange_exchange_bindings")?;
+        self.do_exchange_bind(destination, source, routing_key, options, arguments)
+            .await
+    }
+
+    /// Unbind an exchange from another exchange.
+    ///
+    /// Like [`Channel::exchange_bind`], once connected, it always fails with
+    /// [`Error::UnsupportedByServer`] without contacting the server if the server didn't
+    /// advertise the `exchange_exchange_bindings` capability. There is no way to skip this check.
+    pub async fn exchange_unbind(
+        &self,
+        destination: &str,
+        source: &str,
+        routing_key: &str,
+        options: ExchangeUnbindOptions,
+        arguments: FieldTable,
+    ) -> Result<()> {
+        self.configuration
+            .server_capabilities()
+            .check("exchange_exchange_bindings")?;
+        self.do_exchange_unbind(destination, source, routing_key, options, arguments)
+            .await
+    }
+
+    /// Declare a [priority queue](https://www.rabbitmq.com/priority.html) supporting priorities
+    /// from 0 to `max_priority`.
+    ///
+    /// Messages published to it can be given a priority with [`BasicProperties::with_priority`].
+    pub async fn queue_declare_priority(
+        &self,
+        queue: &str,
+        max_priority: u8,
+        options: QueueDeclareOptions,
+        mut arguments: FieldTable,
+    ) -> Result<Queue> {
+        arguments.insert(
+            "x-max-priority".into(),
+            AMQPValue::ShortShortUInt(max_priority),
+        );
+        self.queue_declare(queue, options, arguments).await
+    }
+
+    /// Declare a queue described by a [`QueueDeclare`] builder.
+    ///
+    /// [`QueueDeclare`]: ./struct.QueueDeclare.html
+    pub async fn declare_queue(&self, declare: QueueDeclare) -> Result<Queue> {
+        let declare = declare.prepare()?;
+        self.queue_declare(declare.queue.as_str(), declare.options, declare.arguments)
+            .await
+    }
+
+    /// Bind several queues, sending all the bindings before waiting for the server to confirm
+    /// them, instead of waiting for a round trip after each one.
+    ///
+    /// Fails with the first error, the remaining bindings are still processed by the server.
+    pub async fn queue_bind_many(&self, binds: Vec<QueueBind>) -> Result<()> {
+        let mut pending = binds
+            .iter()
+            .map(|bind| {
+                Box::pin(self.queue_bind(
+                    bind.queue.as_str(),
+                    bind.exchange.as_str(),
+                    bind.routing_key.as_str(),
+                    bind.options,
+                    bind.arguments.clone(),
+                ))
+            })
+            .collect::<Vec<_>>();
+        poll_fn(|cx| {
+            let mut res = Ok(());
+            pending.retain_mut(|bind| match bind.as_mut().poll(cx) {
+                Poll::Ready(Ok(())) => false,
+                Poll::Ready(Err(error)) => {
+                    if res.is_ok() {
+                        res = Err(error);
+                    }
+                    false
+                }
+                Poll::Pending => true,
+            });
+            if res.is_err() || pending.is_empty() {
+                Poll::Ready(res)
+            } else {
+                Poll::Pending
+            }
+        })
+        .await
+    }
+
+    /// Publish a message to an exchange.
+    ///
+    /// This fails with [`Error::InvalidChannelState`] once the channel is being closed
+    /// gracefully, and with [`Error::ConfirmWindowFull`] in confirm mode when the maximum number
+    /// of unconfirmed publishes is reached, see [`Channel::set_max_unconfirmed`].
+    ///
+    /// Publishing with `immediate` fails with [`Error::UnsupportedByServer`] without contacting
+    /// servers which would close the connection instead, see [`Capabilities::immediate`].
+    ///
+    /// A warning is logged when publishing a transient message to a durable queue through the
+    /// default exchange, as it would be lost if the server restarts.
+    ///
+    /// [`Capabilities::immediate`]: ./struct.Capabilities.html#method.immediate
+    pub async fn basic_publish(
+        &self,
+        exchange: &str,
+        routing_key: &str,
+        options: BasicPublishOptions,
+        payload: &[u8],
+        properties: BasicProperties,
+    ) -> Result<PublisherConfirm> {
+        if self.status.publishing_stopped() {
+            return Err(Error::InvalidChannelState(ChannelState::Closing));
+        }
+        if options.immediate {
+            self.configuration
+                .server_capabilities()
+                .check("immediate")?;
+        }
+        if self.is_transient_publish_to_durable_queue(exchange, routing_key, &properties) {
+            warn!(channel=%self.id, queue=%routing_key, "Publishing a transient message to a durable queue, it will be lost if the server restarts");
+        }
+        let confirm = self
+            .do_basic_publish(exchange, routing_key, options, payload, properties)
+            .await?;
+        self.acknowledgements.record_published();
+        Ok(confirm)
+    }
+
+    pub(crate) fn is_transient_publish_to_durable_queue(
+        &self,
+        exchange: &str,
+        routing_key: &str,
+        properties: &BasicProperties,
+    ) -> bool {
+        exchange.is_empty()
+            && properties.delivery_mode() != &Some(PERSISTENT_DELIVERY_MODE)
+            && self.global_registry.is_queue_durable(routing_key)
+    }
+
+    /// Publish a message with the persistent delivery mode, for it to survive a server restart
+    /// when it gets routed to durable queues.
+    pub async fn basic_publish_persistent(
+        &self,
+        exchange: &str,
+        routing_key: &str,
+        options: BasicPublishOptions,
+        payload: &[u8],
+        properties: BasicProperties,
+    ) -> Result<PublisherConfirm> {
+        self.basic_publish(
+            exchange,
+            routing_key,
+            options,
+            payload,
+            properties.with_delivery_mode(PERSISTENT_DELIVERY_MODE),
+        )
+        .await
+    }
+
+    /// Publish a message which expires after `ttl` if it isn't consumed, the `expiration`
+    /// property is set to its number of milliseconds.
+    ///
+    /// If the queue has a dead letter exchange, expired messages get routed to it with an
+    /// `x-death` reason of `expired`, see [`Delivery::first_death_reason`].
+    ///
+    /// [`Delivery::first_death_reason`]: ./message/struct.Delivery.html#method.first_death_reason
+    pub async fn basic_publish_with_ttl(
+        &self,
+        exchange: &str,
+        routing_key: &str,
+        options: BasicPublishOptions,
+        payload: &[u8],
+        properties: BasicProperties,
+        ttl: Duration,
+    ) -> Result<PublisherConfirm> {
+        self.basic_publish(
+            exchange,
+            routing_key,
+            options,
+            payload,
+            properties.with_expiration(ttl.as_millis().to_string().into()),
+        )
+        .await
+    }
+
+    /// Publish a message directly to a queue, through the default exchange.
+    ///
+    /// Unless `mandatory` is set, the server silently drops messages published to a queue which
+    /// doesn't exist, so a warning is logged if the queue wasn't declared on this connection.
+    pub async fn basic_publish_to_queue(
+        &self,
+        queue: &str,
+        options: BasicPublishOptions,
+        payload: &[u8],
+        properties: BasicProperties,
+    ) -> Result<PublisherConfirm> {
+        if !options.mandatory && !self.global_registry.is_queue_declared(queue) {
+            warn!(channel=%self.id, %queue, "Publishing to a queue which wasn't declared on this connection without mandatory, the message will be lost if it doesn't exist");
+        }
+        self.basic_publish("", queue, options, payload, properties)
+            .await
+    }
+
+    pub(crate) fn stop_publishing(&self) {
+        self.status.stop_publishing();
+    }
+
+    pub(crate) fn pending_confirms(&self) -> usize {
+        self.acknowledgements.pending_count()
+    }
+
     pub async fn wait_for_confirms(&self) -> Result<Vec<BasicReturnMessage>> {
         if let Some(last_pending) = self.acknowledgements.get_last_pending() {
             trace!("Waiting for pending confirms");
@@ -341,6 +769,66 @@ impl Channel {
         Ok(self.returned_messages.drain())
     }
 
+    /// Automatically publish again the messages nacked by the server, up to `max_retries` times
+    /// per message.
+    ///
+    /// This only applies to the messages published in confirm mode after this call, for which we
+    /// then keep a copy until they get acked. The confirmation of a resent message only resolves
+    /// once the server acks it or when we give up on it, in which case it resolves as a nack and
+    /// the message is kept for [`Channel::take_failed_publishes`].
+    pub fn enable_auto_resend(&self, max_retries: usize) {
+        self.acknowledgements.enable_auto_resend(max_retries);
+    }
+
+    /// Refuse new publishes with [`Error::ConfirmWindowFull`] while `max` publisher confirms are
+    /// pending, to bound the number of messages in flight in confirm mode.
+    ///
+    /// Nacked publishes waiting to be resent (see [`Channel::enable_auto_resend`]) keep their
+    /// place in the window, and are only resent once there is room for them.
+    pub fn set_max_unconfirmed(&self, max: usize) {
+        self.acknowledgements.set_max_unconfirmed(max);
+    }
+
+    /// The number of messages published on this channel, and how many of them got confirmed,
+    /// nacked or returned by the server.
+    pub fn confirm_stats(&self) -> ConfirmStats {
+        self.acknowledgements.stats()
+    }
+
+    /// The replies we're waiting for from the server on this channel, in the order we expect
+    /// them, to diagnose a server which answers something else.
+    pub fn pending_answers(&self) -> Vec<String> {
+        self.frames.expected_replies(self.id)
+    }
+
+    /// The delivery tag the next publish will get in confirm mode, to correlate it with its
+    /// confirmation before publishing.
+    ///
+    /// The first publish after [`Channel::confirm_select`] gets the tag 1. Returns 0 if the
+    /// channel isn't in confirm mode.
+    pub fn next_publish_seqno(&self) -> DeliveryTag {
+        if self.status.confirm() {
+            self.acknowledgements.next_delivery_tag()
+        } else {
+            0
+        }
+    }
+
+    /// Take the messages which were still nacked after being resent `max_retries` times.
+    ///
+    /// See [`Channel::enable_auto_resend`].
+    pub fn take_failed_publishes(&self) -> Vec<PublishRequest> {
+        self.acknowledgements.take_failed()
+    }
+
+    /// The delivery tags of the messages delivered to our consumers, or fetched with
+    /// `basic_get`, which have not been acked, nacked nor rejected yet, oldest first.
+    ///
+    /// Those are the messages the server will redeliver if the channel gets closed.
+    pub fn unacked_deliveries(&self) -> Vec<DeliveryTag> {
+        self.delivery_tags.outstanding()
+    }
+
     #[cfg(test)]
     pub(crate) fn register_queue(
         &self,
@@ -353,6 +841,7 @@ impl Channel {
 
     #[cfg(test)]
     pub(crate) fn register_consumer(&self, tag: ShortString, consumer: Consumer) {
+        consumer.set_delivery_tags(self.delivery_tags.clone());
         self.consumers.register(tag, consumer);
     }
 
@@ -362,6 +851,8 @@ impl Channel {
         resolver: PromiseResolver<()>,
         expected_reply: Option<ExpectedReply>,
     ) {
+        trace!(channel=%self.id, ?method, "send method");
+        self.frame_observer.observe(Direction::Outbound, &method);
         self.send_frame(AMQPFrame::Method(self.id, method), resolver, expected_reply);
     }
 
@@ -376,13 +867,36 @@ impl Channel {
         self.wake();
     }
 
-    async fn send_method_frame_with_body(
+    async fn send_method_frame_with_body<R: FnOnce() -> PublishRequest>(
         &self,
         method: AMQPClass,
         payload: &[u8],
         properties: BasicProperties,
-        publisher_confirms_result: Option<PublisherConfirm>,
+        publish_request: Option<R>,
     ) -> Result<PublisherConfirm> {
+        trace!(channel=%self.id, ?method, "send method with body");
+        self.frame_observer.observe(Direction::Outbound, &method);
+        let frames = self.content_frames(method, payload, properties);
+        let (publisher_confirm, promise) = if let Some(publish_request) = publish_request {
+            self.acknowledgements
+                .register_pending(publish_request, || self.frames.push_frames(frames))?
+        } else {
+            (
+                PublisherConfirm::not_requested(self.returned_messages.clone()),
+                self.frames.push_frames(frames),
+            )
+        };
+        self.wake();
+        promise.await?;
+        Ok(publisher_confirm)
+    }
+
+    fn content_frames(
+        &self,
+        method: AMQPClass,
+        payload: &[u8],
+        properties: BasicProperties,
+    ) -> Vec<AMQPFrame> {
         let class_id = method.get_amqp_class_id();
         let header = AMQPContentHeader {
             class_id,
@@ -401,13 +915,7 @@ impl Channel {
                 .chunks(frame_max as usize - 8)
                 .map(|chunk| AMQPFrame::Body(self.id, chunk.into())),
         );
-
-        trace!(channel=%self.id, "send_frames");
-        let promise = self.frames.push_frames(frames);
-        self.wake();
-        promise.await?;
-        Ok(publisher_confirms_result
-            .unwrap_or_else(|| PublisherConfirm::not_requested(self.returned_messages.clone())))
+        frames
     }
 
     fn handle_invalid_contents(
@@ -501,14 +1009,53 @@ impl Channel {
         }
     }
 
-    fn before_basic_publish(&self) -> Option<PublisherConfirm> {
+    // The publish gets registered for a confirmation when its frames are sent, the request is
+    // only built if it has to be kept to resend it
+    fn before_basic_publish<'a>(
+        &self,
+        exchange: &'a str,
+        routing_key: &'a str,
+        options: BasicPublishOptions,
+        payload: &'a [u8],
+        properties: &BasicProperties,
+    ) -> Option<impl FnOnce() -> PublishRequest + Send + 'a> {
         if self.status.confirm() {
-            Some(self.acknowledgements.register_pending())
+            let properties = properties.clone();
+            Some(move || PublishRequest {
+                exchange: exchange.into(),
+                routing_key: routing_key.into(),
+                options,
+                payload: payload.into(),
+                properties,
+            })
         } else {
             None
         }
     }
 
+    fn resend_nacked(&self) {
+        let promises = self.acknowledgements.resend_nacked(|request| {
+            let method = AMQPClass::Basic(protocol::basic::AMQPMethod::Publish(
+                protocol::basic::Publish {
+                    exchange: request.exchange.clone(),
+                    routing_key: request.routing_key.clone(),
+                    mandatory: request.options.mandatory,
+                    immediate: request.options.immediate,
+                },
+            ));
+            trace!(channel=%self.id, ?method, "resend method with body");
+            let frames = self.content_frames(method, &request.payload, request.properties.clone());
+            self.frames.push_frames(frames)
+        });
+        if promises.is_empty() {
+            return;
+        }
+        for promise in promises {
+            self.internal_rpc.register_internal_future(promise);
+        }
+        self.wake();
+    }
+
     fn before_basic_cancel(&self, consumer_tag: &str) {
         self.consumers.start_cancel_one(consumer_tag);
     }
@@ -579,21 +1126,38 @@ impl Channel {
     }
 
     fn on_basic_recover_async_sent(&self) {
+        self.delivery_tags.clear();
         self.consumers.drop_prefetched_messages();
     }
 
+    // Only forget the delivery tags once the frame is on its way, so that a failed ack can be
+    // retried
     fn on_basic_ack_sent(&self, multiple: bool, delivery_tag: DeliveryTag) {
-        if multiple && delivery_tag == 0 {
-            self.consumers.drop_prefetched_messages();
-        }
+        self.delivery_tags.settle(delivery_tag, multiple);
+        self.drop_settled_prefetched_messages(multiple, delivery_tag);
     }
 
     fn on_basic_nack_sent(&self, multiple: bool, delivery_tag: DeliveryTag) {
-        if multiple && delivery_tag == 0 {
-            self.consumers.drop_prefetched_messages();
+        self.delivery_tags.settle(delivery_tag, multiple);
+        self.drop_settled_prefetched_messages(multiple, delivery_tag);
+    }
+
+    // The messages covered by a multiple ack or nack must not be handed to the consumers
+    // anymore
+    fn drop_settled_prefetched_messages(&self, multiple: bool, delivery_tag: DeliveryTag) {
+        if multiple {
+            if delivery_tag == 0 {
+                self.consumers.drop_prefetched_messages();
+            } else {
+                self.consumers.drop_prefetched_messages_before(delivery_tag);
+            }
         }
     }
 
+    fn on_basic_reject_sent(&self, delivery_tag: DeliveryTag) {
+        self.delivery_tags.settle(delivery_tag, false);
+    }
+
     fn tune_connection_configuration(
         &self,
         channel_max: ChannelId,
@@ -635,6 +1199,9 @@ impl Channel {
 
     fn on_connection_start_received(&self, method: protocol::connection::Start) -> Result<()> {
         trace!(?method, "Server sent connection::Start");
+        self.configuration
+            .server_capabilities()
+            .set_server_properties(&method.server_properties);
         let state = self.connection_status.state();
         let step = self.connection_status.connection_step_name();
         if let (
@@ -658,6 +1225,10 @@ impl Channel {
                 .any(|m| m == mechanism_str)
             {
                 error!(%mechanism, "unsupported mechanism");
+                let error = Error::UnsupportedSASLMechanism(mechanism);
+                resolver.swear(Err(error.clone()));
+                self.internal_rpc.set_connection_error(error.clone());
+                return Err(error);
             }
             if !method
                 .locales
@@ -880,7 +1451,8 @@ impl Channel {
         method: protocol::channel::FlowOk,
         resolver: PromiseResolver<Boolean>,
     ) -> Result<()> {
-        // Nothing to do here, the server just confirmed that we paused/resumed the receiving flow
+        // The server just confirmed that we paused/resumed the receiving flow
+        self.status.set_receive_flow(method.active);
         resolver.swear(Ok(method.active));
         Ok(())
     }
@@ -942,8 +1514,11 @@ impl Channel {
         options: ExchangeDeclareOptions,
         arguments: FieldTable,
     ) -> Result<()> {
-        self.global_registry
-            .register_exchange(exchange, kind, options, arguments);
+        // Passive declarations only check for existence, they're not part of our topology
+        if !options.passive {
+            self.global_registry
+                .register_exchange(exchange, kind, options, arguments);
+        }
         resolver.swear(Ok(()));
         Ok(())
     }
@@ -978,15 +1553,26 @@ impl Channel {
         &self,
         method: protocol::queue::DeclareOk,
         resolver: PromiseResolver<Queue>,
+        queue: ShortString,
         options: QueueDeclareOptions,
         arguments: FieldTable,
     ) -> Result<()> {
-        if options.exclusive {
-            self.local_registry
-                .register_queue(method.queue.clone(), options, arguments.clone());
+        if queue.as_str().is_empty() && !method.queue.as_str().is_empty() {
+            self.status
+                .set_last_generated_queue_name(method.queue.clone());
+        }
+        // Passive declarations only check for existence, they're not part of our topology
+        if !options.passive {
+            if options.exclusive {
+                self.local_registry.register_queue(
+                    method.queue.clone(),
+                    options,
+                    arguments.clone(),
+                );
+            }
+            self.global_registry
+                .register_queue(method.queue.clone(), options, arguments);
         }
-        self.global_registry
-            .register_queue(method.queue.clone(), options, arguments);
         resolver.swear(Ok(Queue::new(
             method.queue,
             method.message_count,
@@ -1043,6 +1629,9 @@ impl Channel {
         options: BasicGetOptions,
     ) -> Result<()> {
         let class_id = method.get_amqp_class_id();
+        if !options.no_ack {
+            self.delivery_tags.register(method.delivery_tag, None);
+        }
         self.basic_get_delivery.start_new_delivery(
             queue,
             options,
@@ -1099,6 +1688,7 @@ impl Channel {
             )
         });
         let external_consumer = consumer.external(self.id, self.internal_rpc.clone());
+        consumer.set_delivery_tags(self.delivery_tags.clone());
         self.consumers.register(method.consumer_tag, consumer);
         resolver.swear(Ok(external_consumer));
         Ok(())
@@ -1107,17 +1697,24 @@ impl Channel {
     fn on_basic_deliver_received(&self, method: protocol::basic::Deliver) -> Result<()> {
         let class_id = method.get_amqp_class_id();
         let consumer_tag = method.consumer_tag.clone();
-        self.consumers.start_delivery(&consumer_tag, |error| {
-            Delivery::new(
-                self.id,
-                method.delivery_tag,
-                method.exchange,
-                method.routing_key,
-                method.redelivered,
-                Some(self.internal_rpc.clone()),
-                Some(error),
-            )
-        });
+        self.consumers
+            .start_delivery(&consumer_tag, |error, no_ack| {
+                // The server doesn't expect any ack for no_ack consumers, make the Acker a no-op
+                let (internal_rpc, error) = if no_ack {
+                    (None, None)
+                } else {
+                    (Some(self.internal_rpc.clone()), Some(error))
+                };
+                Delivery::new(
+                    self.id,
+                    method.delivery_tag,
+                    method.exchange,
+                    method.routing_key,
+                    method.redelivered,
+                    internal_rpc,
+                    error,
+                )
+            });
         self.status
             .set_will_receive(class_id, DeliveryCause::Consume(consumer_tag));
         Ok(())
@@ -1166,6 +1763,8 @@ impl Channel {
                         )
                     })?;
             }
+            // Acks make room in the window for the publishes waiting to be resent
+            self.resend_nacked();
         }
         Ok(())
     }
@@ -1197,12 +1796,14 @@ impl Channel {
                         )
                     })?;
             }
+            self.resend_nacked();
         }
         Ok(())
     }
 
     fn on_basic_return_received(&self, method: protocol::basic::Return) -> Result<()> {
         let class_id = method.get_amqp_class_id();
+        self.acknowledgements.record_returned();
         self.returned_messages
             .start_new_delivery(BasicReturnMessage::new(
                 method.exchange,
@@ -1216,6 +1817,7 @@ impl Channel {
     }
 
     fn on_basic_recover_ok_received(&self) -> Result<()> {
+        self.delivery_tags.clear();
         self.consumers.drop_prefetched_messages();
         Ok(())
     }
@@ -1,6 +1,6 @@
 use crate::{
     channel_receiver_state::{ChannelReceiverStates, DeliveryCause},
-    types::{ChannelId, Identifier, PayloadSize},
+    types::{ChannelId, Identifier, PayloadSize, ShortString},
     Result,
 };
 use parking_lot::Mutex;
@@ -110,6 +110,35 @@ impl ChannelStatus {
     pub(crate) fn flow(&self) -> bool {
         self.0.lock().send_flow
     }
+
+    /// Whether the server delivers messages on this channel, see [`Channel::channel_flow`].
+    ///
+    /// [`Channel::channel_flow`]: ./struct.Channel.html#method.channel_flow
+    pub fn receive_flow(&self) -> bool {
+        self.0.lock().receive_flow
+    }
+
+    pub(crate) fn set_receive_flow(&self, flow: bool) {
+        self.0.lock().receive_flow = flow;
+    }
+
+    /// The name the server generated for the last queue declared with an empty name on this
+    /// channel.
+    pub fn last_generated_queue_name(&self) -> Option<ShortString> {
+        self.0.lock().last_generated_queue_name.clone()
+    }
+
+    pub(crate) fn set_last_generated_queue_name(&self, queue: ShortString) {
+        self.0.lock().last_generated_queue_name = Some(queue);
+    }
+
+    pub(crate) fn publishing_stopped(&self) -> bool {
+        self.0.lock().publishing_stopped
+    }
+
+    pub(crate) fn stop_publishing(&self) {
+        self.0.lock().publishing_stopped = true;
+    }
 }
 
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
@@ -130,7 +159,13 @@ impl fmt::Debug for ChannelStatus {
                 .field("state", &inner.state)
                 .field("receiver_state", &inner.receiver_state)
                 .field("confirm", &inner.confirm)
-                .field("send_flow", &inner.send_flow);
+                .field("send_flow", &inner.send_flow)
+                .field("receive_flow", &inner.receive_flow)
+                .field(
+                    "last_generated_queue_name",
+                    &inner.last_generated_queue_name,
+                )
+                .field("publishing_stopped", &inner.publishing_stopped);
         }
         debug.finish()
     }
@@ -139,6 +174,9 @@ impl fmt::Debug for ChannelStatus {
 struct Inner {
     confirm: bool,
     send_flow: bool,
+    receive_flow: bool,
+    last_generated_queue_name: Option<ShortString>,
+    publishing_stopped: bool,
     state: ChannelState,
     receiver_state: ChannelReceiverStates,
 }
@@ -148,6 +186,9 @@ impl Default for Inner {
         Self {
             confirm: false,
             send_flow: true,
+            receive_flow: true,
+            last_generated_queue_name: None,
+            publishing_stopped: false,
             state: ChannelState::default(),
             receiver_state: ChannelReceiverStates::default(),
         }
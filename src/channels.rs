@@ -1,6 +1,7 @@
 use crate::{
     connection_closer::ConnectionCloser,
     error_handler::ErrorHandler,
+    frame_observer::{Direction, FrameObserver},
     frames::Frames,
     id_sequence::IdSequence,
     internal_rpc::InternalRPCHandle,
@@ -27,6 +28,7 @@ pub(crate) struct Channels {
     executor: Arc<dyn FullExecutor + Send + Sync>,
     frames: Frames,
     error_handler: ErrorHandler,
+    frame_observer: FrameObserver,
 }
 
 impl Channels {
@@ -39,14 +41,20 @@ impl Channels {
         frames: Frames,
         executor: Arc<dyn FullExecutor + Send + Sync>,
     ) -> Self {
+        let frame_observer = FrameObserver::default();
         Self {
-            inner: Arc::new(Mutex::new(Inner::new(configuration, waker))),
+            inner: Arc::new(Mutex::new(Inner::new(
+                configuration,
+                waker,
+                frame_observer.clone(),
+            ))),
             connection_status,
             global_registry,
             internal_rpc,
             executor,
             frames,
             error_handler: ErrorHandler::default(),
+            frame_observer,
         }
     }
 
@@ -80,6 +88,25 @@ impl Channels {
         self.inner.lock().channels.get(&id).cloned()
     }
 
+    pub(crate) fn states(&self) -> Vec<(ChannelId, ChannelState)> {
+        let mut states = self
+            .inner
+            .lock()
+            .channels
+            .iter()
+            .filter(|(id, _)| **id != 0)
+            .map(|(id, channel)| (*id, channel.status().state()))
+            .collect::<Vec<_>>();
+        states.sort_by_key(|(id, _)| *id);
+        states
+    }
+
+    pub(crate) fn reset(&self) {
+        for channel in self.inner.lock().channels.values() {
+            channel.reset();
+        }
+    }
+
     pub(crate) fn remove(&self, id: ChannelId, error: Error) -> Result<()> {
         self.frames.clear_expected_replies(id, error);
         if self.inner.lock().channels.remove(&id).is_some() {
@@ -90,6 +117,8 @@ impl Channels {
     }
 
     pub(crate) fn receive_method(&self, id: ChannelId, method: AMQPClass) -> Result<()> {
+        trace!(channel=%id, ?method, "receive method");
+        self.frame_observer.observe(Direction::Inbound, &method);
         self.get(id)
             .map(|channel| channel.receive_method(method))
             .unwrap_or_else(|| Err(Error::InvalidChannel(id)))
@@ -263,6 +292,13 @@ impl Channels {
         self.error_handler.set_handler(handler);
     }
 
+    pub(crate) fn set_frame_observer<O: Fn(Direction, &AMQPClass) + Send + Sync + 'static>(
+        &self,
+        observer: O,
+    ) {
+        self.frame_observer.set_observer(observer);
+    }
+
     pub(crate) fn topology(&self) -> Vec<ChannelDefinitionInternal> {
         self.inner
             .lock()
@@ -296,15 +332,21 @@ struct Inner {
     channel_id: IdSequence<ChannelId>,
     configuration: Configuration,
     waker: SocketStateHandle,
+    frame_observer: FrameObserver,
 }
 
 impl Inner {
-    fn new(configuration: Configuration, waker: SocketStateHandle) -> Self {
+    fn new(
+        configuration: Configuration,
+        waker: SocketStateHandle,
+        frame_observer: FrameObserver,
+    ) -> Self {
         Self {
             channels: HashMap::default(),
             channel_id: IdSequence::new(false),
             configuration,
             waker,
+            frame_observer,
         }
     }
 
@@ -327,6 +369,7 @@ impl Inner {
             self.waker.clone(),
             internal_rpc,
             frames,
+            self.frame_observer.clone(),
             executor,
             connection_closer,
         );
@@ -1,4 +1,5 @@
 use crate::{
+    capabilities::Capabilities,
     protocol,
     types::{ChannelId, FrameSize, Heartbeat},
 };
@@ -8,6 +9,7 @@ use std::{fmt, sync::Arc};
 #[derive(Clone, Default)]
 pub struct Configuration {
     inner: Arc<RwLock<Inner>>,
+    server_capabilities: Capabilities,
 }
 
 impl Configuration {
@@ -35,6 +37,10 @@ impl Configuration {
     pub(crate) fn set_heartbeat(&self, heartbeat: Heartbeat) {
         self.inner.write().heartbeat = heartbeat;
     }
+
+    pub fn server_capabilities(&self) -> &Capabilities {
+        &self.server_capabilities
+    }
 }
 
 #[derive(Default)]
@@ -51,6 +57,7 @@ impl fmt::Debug for Configuration {
             .field("channel_max", &inner.channel_max)
             .field("frame_max", &inner.frame_max)
             .field("heartbeat", &inner.heartbeat)
+            .field("server_capabilities", &self.server_capabilities)
             .finish()
     }
 }
@@ -1,7 +1,8 @@
 use crate::types::{AMQPValue, FieldTable, LongString};
 use executor_trait::FullExecutor;
 use reactor_trait::Reactor;
-use std::sync::Arc;
+use socket2::{SockRef, TcpKeepalive};
+use std::{io, net::TcpStream, sync::Arc, time::Duration};
 
 #[derive(Clone)]
 pub struct ConnectionProperties {
@@ -9,6 +10,15 @@ pub struct ConnectionProperties {
     pub client_properties: FieldTable,
     pub executor: Option<Arc<dyn FullExecutor + Send + Sync>>,
     pub reactor: Option<Arc<dyn Reactor + Send + Sync>>,
+    /// Disable Nagle's algorithm on the socket, `true` by default.
+    ///
+    /// AMQP methods are sent in small frames which would otherwise be delayed until the
+    /// previous ones got acknowledged by the server, adding latency to every request.
+    pub tcp_nodelay: bool,
+    /// Enable TCP keepalive on the socket, sending the first probe after the connection was
+    /// idle for this long, to detect dead peers even without heartbeats.
+    pub tcp_keepalive: Option<Duration>,
+    pub(crate) read_timeout: Option<Duration>,
 }
 
 impl Default for ConnectionProperties {
@@ -18,6 +28,9 @@ impl Default for ConnectionProperties {
             client_properties: FieldTable::default(),
             executor: None,
             reactor: None,
+            tcp_nodelay: true,
+            tcp_keepalive: None,
+            read_timeout: None,
         }
     }
 }
@@ -43,4 +56,66 @@ impl ConnectionProperties {
         self.reactor = Some(Arc::new(reactor));
         self
     }
+
+    #[must_use]
+    pub fn with_tcp_nodelay(mut self, tcp_nodelay: bool) -> Self {
+        self.tcp_nodelay = tcp_nodelay;
+        self
+    }
+
+    #[must_use]
+    pub fn with_tcp_keepalive(mut self, tcp_keepalive: Duration) -> Self {
+        self.tcp_keepalive = Some(tcp_keepalive);
+        self
+    }
+
+    /// Fail the connection with [`Error::ReadTimeout`] when nothing, not even a heartbeat, is
+    /// received from the server for this long once connected.
+    ///
+    /// [`Error::ReadTimeout`]: ./enum.Error.html#variant.ReadTimeout
+    #[must_use]
+    pub fn with_read_timeout(mut self, read_timeout: Duration) -> Self {
+        self.read_timeout = Some(read_timeout);
+        self
+    }
+
+    pub(crate) fn configure_socket(&self, socket: &TcpStream) -> io::Result<()> {
+        socket.set_nodelay(self.tcp_nodelay)?;
+        if let Some(keepalive) = self.tcp_keepalive {
+            SockRef::from(socket).set_tcp_keepalive(&TcpKeepalive::new().with_time(keepalive))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+
+    #[test]
+    fn configure_socket() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let socket = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+
+        ConnectionProperties::default()
+            .configure_socket(&socket)
+            .unwrap();
+        assert!(socket.nodelay().unwrap());
+        assert!(!SockRef::from(&socket).keepalive().unwrap());
+
+        ConnectionProperties::default()
+            .with_tcp_nodelay(false)
+            .with_tcp_keepalive(Duration::from_secs(30))
+            .configure_socket(&socket)
+            .unwrap();
+        assert!(!socket.nodelay().unwrap());
+        let socket = SockRef::from(&socket);
+        assert!(socket.keepalive().unwrap());
+        #[cfg(target_os = "linux")]
+        assert_eq!(
+            socket.tcp_keepalive_time().unwrap(),
+            Duration::from_secs(30)
+        );
+    }
 }
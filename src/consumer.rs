@@ -1,13 +1,15 @@
 use crate::{
+    auto_ack::AutoAck,
     channel_closer::ChannelCloser,
     consumer_canceler::ConsumerCanceler,
     consumer_status::{ConsumerState, ConsumerStatus},
+    delivery_tags::DeliveryTags,
     error_holder::ErrorHolder,
     internal_rpc::InternalRPCHandle,
     message::{Delivery, DeliveryResult},
     options::BasicConsumeOptions,
-    types::{ChannelId, PayloadSize},
-    types::{FieldTable, ShortString},
+    types::{AMQPValue, ChannelId, DeliveryTag, PayloadSize},
+    types::{FieldTable, LongInt, LongLongInt, ShortString, Timestamp},
     wakers::Wakers,
     BasicProperties, Error, Result,
 };
@@ -30,6 +32,15 @@ pub trait ConsumerDelegate: Send + Sync {
     fn drop_prefetched_messages(&self) -> Pin<Box<dyn Future<Output = ()> + Send>> {
         Box::pin(async move {})
     }
+
+    /// Called when the consumer stops because of an error, such as a malformed delivery or the
+    /// connection going down, as opposed to a cancellation.
+    ///
+    /// `on_new_delivery` still gets called with `Ok(None)` afterwards, as the consumer is
+    /// canceled. Passes the error to `on_new_delivery` by default.
+    fn on_error(&self, error: Error) -> Pin<Box<dyn Future<Output = ()> + Send>> {
+        self.on_new_delivery(Err(error))
+    }
 }
 
 impl<
@@ -67,7 +78,7 @@ impl<
 ///
 /// * If the flag [`BasicConsumeOptions::no_ack`] is set to `true` while obtaining the consumer from
 ///   [`Channel::basic_consume`], the server implicitely acknowledges each message after it has been
-///   sent.
+///   sent. Acking, nacking or rejecting those messages is then a no-op.
 /// * If the flag [`BasicConsumeOptions::no_ack`] is set to `false`, a message has to be explicitly
 ///   acknowledged or rejected with [`Acker::ack`],
 ///   [`Acker::nack`] or [`Acker::reject`]. See the documentation at [`Delivery`]
@@ -81,6 +92,12 @@ impl<
 /// To limit the maximum number of unacknowledged messages arriving, you can call [`Channel::basic_qos`]
 /// before creating the consumer.
 ///
+/// The consumer buffers every delivery the server sends until it is consumed, without ever
+/// dropping any. Setting a prefetch count is thus the way to bound this buffer and get
+/// back-pressure from a slow handler: once the limit is reached, the server stops delivering
+/// until some messages get acknowledged. [`in_flight`] gives the current number of
+/// unacknowledged deliveries.
+///
 /// Also see the RabbitMQ documentation about
 /// [Consumer Prefetch](https://www.rabbitmq.com/consumer-prefetch.html).
 ///
@@ -133,6 +150,7 @@ impl<
 /// [`DeliveryResult`]: ./message/type.DeliveryResult.html
 /// [`BasicConsumeOptions::no_ack`]: ./options/struct.BasicConsumeOptions.html#structfield.no_ack
 /// [`set_delegate`]: #method.set_delegate
+/// [`in_flight`]: #method.in_flight
 #[derive(Clone)]
 pub struct Consumer {
     inner: Arc<Mutex<ConsumerInner>>,
@@ -233,12 +251,49 @@ impl Consumer {
         status.set_delegate();
     }
 
+    /// Ack the deliveries once `handler` successfully processed them, requeue them once when
+    /// it fails and then dead-letter them.
+    ///
+    /// This is a shortcut for setting an [`AutoAck`] delegate.
+    ///
+    /// [`AutoAck`]: ./struct.AutoAck.html
+    pub fn auto_ack_on_success<
+        E: fmt::Display,
+        H: Fn(Delivery) -> std::result::Result<(), E> + Send + Sync + 'static,
+    >(
+        &self,
+        handler: H,
+    ) {
+        self.set_delegate(AutoAck::new(handler));
+    }
+
     pub(crate) fn reset(&self) {
         self.inner.lock().reset(self.options.no_ack);
     }
 
-    pub(crate) fn start_new_delivery(&self, delivery: Delivery) {
-        self.inner.lock().current_message = Some(delivery);
+    /// Get the number of deliveries received by this consumer which haven't been acked, nacked
+    /// or rejected yet.
+    ///
+    /// This is always 0 for consumers created with [`BasicConsumeOptions::no_ack`].
+    pub fn in_flight(&self) -> usize {
+        let inner = self.inner.lock();
+        inner.delivery_tags.delivered_to(inner.tag.as_str())
+    }
+
+    // Deliveries are tracked by the channel, which needs them to be settled once and only once
+    pub(crate) fn set_delivery_tags(&self, delivery_tags: DeliveryTags) {
+        self.inner.lock().delivery_tags = delivery_tags;
+    }
+
+    pub(crate) fn start_new_delivery(&self, mut delivery: Delivery) {
+        let mut inner = self.inner.lock();
+        delivery.queue = self.queue.clone();
+        if !self.options.no_ack {
+            inner
+                .delivery_tags
+                .register(delivery.delivery_tag, Some(inner.tag.clone()));
+        }
+        inner.current_message = Some(delivery);
     }
 
     pub(crate) fn handle_content_header_frame(
@@ -259,6 +314,12 @@ impl Consumer {
         self.inner.lock().drop_prefetched_messages();
     }
 
+    pub(crate) fn drop_prefetched_messages_before(&self, delivery_tag: DeliveryTag) {
+        self.inner
+            .lock()
+            .drop_prefetched_messages_before(delivery_tag);
+    }
+
     pub(crate) fn start_cancel(&self) {
         self.status.lock().start_cancel();
     }
@@ -275,6 +336,7 @@ impl Consumer {
 struct ConsumerInner {
     status: ConsumerStatus,
     current_message: Option<Delivery>,
+    delivery_tags: DeliveryTags,
     deliveries_in: Sender<DeliveryResult>,
     deliveries_out: Receiver<DeliveryResult>,
     wakers: Wakers,
@@ -315,6 +377,7 @@ impl ConsumerInner {
         Self {
             status,
             current_message: None,
+            delivery_tags: DeliveryTags::default(),
             deliveries_in: sender,
             deliveries_out: receiver,
             wakers: Wakers::default(),
@@ -379,6 +442,24 @@ impl ConsumerInner {
         while self.next_delivery().is_some() {}
     }
 
+    fn drop_prefetched_messages_before(&mut self, delivery_tag: DeliveryTag) {
+        trace!(consumer_tag=%self.tag, delivery_tag, "drop_prefetched_messages_before");
+        // Deliveries are handed to the delegate as soon as they're complete, there is nothing
+        // prefetched in that case
+        let kept = self
+            .deliveries_out
+            .try_iter()
+            .filter(|delivery| {
+                !matches!(delivery, Ok(Some(delivery)) if delivery.delivery_tag <= delivery_tag)
+            })
+            .collect::<Vec<_>>();
+        for delivery in kept {
+            self.deliveries_in
+                .send(delivery)
+                .expect("failed to send delivery to consumer");
+        }
+    }
+
     fn cancel(&mut self) {
         trace!(consumer_tag=%self.tag, "cancel");
         let mut status = self.status.lock();
@@ -399,7 +480,7 @@ impl ConsumerInner {
         self.error.set(error.clone());
         if let Some(delegate) = self.delegate.as_ref() {
             let delegate = delegate.clone();
-            self.executor.spawn(delegate.on_new_delivery(Err(error)));
+            self.executor.spawn(delegate.on_error(error));
         } else {
             self.deliveries_in
                 .send(Err(error))
@@ -443,6 +524,129 @@ impl Stream for Consumer {
     }
 }
 
+/// Several consumers on the same channel, merged into a single stream of deliveries.
+///
+/// Each delivery carries the name of the queue it comes from in [`Delivery::queue`]. The
+/// stream ends once all the consumers have been canceled.
+///
+/// [`Delivery::queue`]: ./message/struct.Delivery.html#structfield.queue
+#[derive(Debug)]
+pub struct MultiConsumer {
+    consumers: Vec<Consumer>,
+    done: Vec<bool>,
+    next: usize,
+}
+
+impl MultiConsumer {
+    pub(crate) fn new(consumers: Vec<Consumer>) -> Self {
+        let done = vec![false; consumers.len()];
+        Self {
+            consumers,
+            done,
+            next: 0,
+        }
+    }
+
+    /// The underlying consumers, to get the tag used for each queue.
+    pub fn consumers(&self) -> &[Consumer] {
+        &self.consumers
+    }
+}
+
+impl Stream for MultiConsumer {
+    type Item = Result<Delivery>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        let count = this.consumers.len();
+        // Start from a different consumer each time so that a busy queue can't starve the others
+        for offset in 0..count {
+            let index = (this.next + offset) % count;
+            if this.done[index] {
+                continue;
+            }
+            match Pin::new(&mut this.consumers[index]).poll_next(cx) {
+                Poll::Ready(Some(delivery)) => {
+                    this.next = (index + 1) % count;
+                    return Poll::Ready(Some(delivery));
+                }
+                Poll::Ready(None) => this.done[index] = true,
+                Poll::Pending => {}
+            }
+        }
+        if this.done.iter().all(|done| *done) {
+            Poll::Ready(None)
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+/// Typed builder for the most common `basic_consume` arguments.
+///
+/// ```rust
+/// use lapin::{types::FieldTable, ConsumerArguments, StreamOffset};
+///
+/// let arguments: FieldTable = ConsumerArguments::default()
+///     .priority(10)
+///     .stream_offset(StreamOffset::First)
+///     .into();
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ConsumerArguments(FieldTable);
+
+/// Where to start consuming a [stream](https://www.rabbitmq.com/streams.html) from.
+#[derive(Clone, Debug, PartialEq)]
+pub enum StreamOffset {
+    /// The first message available in the stream.
+    First,
+    /// The last chunk of messages written to the stream.
+    Last,
+    /// The messages written to the stream after we start consuming.
+    Next,
+    /// The message at this offset.
+    Offset(LongLongInt),
+    /// The first message written at or after this timestamp, in seconds.
+    Timestamp(Timestamp),
+}
+
+impl ConsumerArguments {
+    /// Deliver messages to this consumer before the ones with a lower priority (`x-priority`).
+    #[must_use]
+    pub fn priority(self, priority: LongInt) -> Self {
+        self.argument("x-priority", AMQPValue::LongInt(priority))
+    }
+
+    /// Start consuming a stream from the given offset (`x-stream-offset`).
+    #[must_use]
+    pub fn stream_offset(self, offset: StreamOffset) -> Self {
+        let value = match offset {
+            StreamOffset::First => AMQPValue::LongString("first".into()),
+            StreamOffset::Last => AMQPValue::LongString("last".into()),
+            StreamOffset::Next => AMQPValue::LongString("next".into()),
+            StreamOffset::Offset(offset) => AMQPValue::LongLongInt(offset),
+            StreamOffset::Timestamp(timestamp) => AMQPValue::Timestamp(timestamp),
+        };
+        self.argument("x-stream-offset", value)
+    }
+
+    /// Get the arguments to pass to `basic_consume`.
+    pub fn into_field_table(self) -> FieldTable {
+        self.0
+    }
+
+    fn argument(mut self, key: &str, value: AMQPValue) -> Self {
+        self.0.insert(key.into(), value);
+        self
+    }
+}
+
+impl From<ConsumerArguments> for FieldTable {
+    fn from(arguments: ConsumerArguments) -> Self {
+        arguments.into_field_table()
+    }
+}
+
 #[cfg(test)]
 mod futures_tests {
     use super::*;
@@ -530,4 +734,426 @@ mod futures_tests {
             );
         }
     }
+
+    #[test]
+    fn consumer_arguments() {
+        let arguments = ConsumerArguments::default().priority(-5).into_field_table();
+        assert_eq!(
+            arguments.inner().get("x-priority"),
+            Some(&AMQPValue::LongInt(-5))
+        );
+
+        for (offset, value) in [
+            (StreamOffset::First, AMQPValue::LongString("first".into())),
+            (StreamOffset::Last, AMQPValue::LongString("last".into())),
+            (StreamOffset::Next, AMQPValue::LongString("next".into())),
+            (StreamOffset::Offset(42), AMQPValue::LongLongInt(42)),
+            (
+                StreamOffset::Timestamp(1_700_000_000),
+                AMQPValue::Timestamp(1_700_000_000),
+            ),
+        ] {
+            let arguments = FieldTable::from(ConsumerArguments::default().stream_offset(offset));
+            assert_eq!(arguments.inner().len(), 1);
+            assert_eq!(arguments.inner().get("x-stream-offset"), Some(&value));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::connection::test_utils::*;
+    use crate::options::BasicConsumeOptions;
+    use crate::types::{FieldTable, ShortString};
+    use crate::BasicProperties;
+    use amq_protocol::frame::{AMQPContentHeader, AMQPFrame};
+    use amq_protocol::protocol::{basic, AMQPClass};
+
+    #[test]
+    fn consumer_in_flight_accounting() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        use crate::consumer::Consumer;
+        use crate::options::{BasicAckOptions, BasicRejectOptions};
+        use futures_lite::future::{block_on, poll_once};
+
+        let (conn, channel) = connected_channel();
+        let queue_name = ShortString::from("consumed");
+        let consumer_tag = ShortString::from("consumer-tag");
+        let consumer = Consumer::new(
+            consumer_tag.clone(),
+            Arc::new(async_global_executor_trait::AsyncGlobalExecutor),
+            None,
+            queue_name.clone(),
+            BasicConsumeOptions::default(),
+            FieldTable::default(),
+        );
+        channel.register_consumer(consumer_tag.clone(), consumer.clone());
+
+        for delivery_tag in 1..=3 {
+            deliver(&conn, &channel, consumer_tag.as_str(), delivery_tag, b"");
+        }
+        assert_eq!(consumer.in_flight(), 3);
+        assert_eq!(channel.in_flight(), 3);
+
+        let _ = block_on(poll_once(
+            channel.basic_ack(2, BasicAckOptions { multiple: true }),
+        ));
+        assert_eq!(consumer.in_flight(), 1);
+
+        let _ = block_on(poll_once(
+            channel.basic_reject(3, BasicRejectOptions::default()),
+        ));
+        assert_eq!(consumer.in_flight(), 0);
+        assert_eq!(channel.in_flight(), 0);
+    }
+
+    #[test]
+    fn consumer_delegate_on_error() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        use crate::consumer::{Consumer, ConsumerDelegate};
+        use crate::message::DeliveryResult;
+        use std::{pin::Pin, time::Duration};
+
+        struct Delegate(flume::Sender<&'static str>);
+
+        impl ConsumerDelegate for Delegate {
+            fn on_new_delivery(
+                &self,
+                delivery: DeliveryResult,
+            ) -> Pin<Box<dyn Future<Output = ()> + Send>> {
+                let event = match delivery {
+                    Ok(Some(_)) => "delivery",
+                    Ok(None) => "canceled",
+                    Err(_) => "error delivery",
+                };
+                let events = self.0.clone();
+                Box::pin(async move { events.send(event).unwrap() })
+            }
+
+            fn on_error(&self, error: Error) -> Pin<Box<dyn Future<Output = ()> + Send>> {
+                assert!(matches!(error, Error::ProtocolError(_)));
+                let events = self.0.clone();
+                Box::pin(async move { events.send("error").unwrap() })
+            }
+        }
+
+        let (conn, channel) = connected_channel();
+        let consumer = Consumer::new(
+            "consumer-tag".into(),
+            Arc::new(async_global_executor_trait::AsyncGlobalExecutor),
+            None,
+            "consumed".into(),
+            BasicConsumeOptions::default(),
+            FieldTable::default(),
+        );
+        let (sender, events) = flume::unbounded();
+        consumer.set_delegate(Delegate(sender));
+        channel.register_consumer("consumer-tag".into(), consumer);
+
+        // The body is larger than announced by the content header
+        for frame in deliver_frames(channel.id(), "consumer-tag", 1, b"{}")
+            .into_iter()
+            .take(2)
+        {
+            handle_frame(&conn, frame).unwrap();
+        }
+        assert!(handle_frame(&conn, AMQPFrame::Body(channel.id(), b"{}}".to_vec())).is_err());
+
+        // Both are spawned on the executor, the order isn't guaranteed
+        let mut received = (0..2)
+            .map(|_| events.recv_timeout(Duration::from_secs(1)).unwrap())
+            .collect::<Vec<_>>();
+        received.sort_unstable();
+        assert_eq!(received, ["canceled", "error"]);
+        assert!(events.try_recv().is_err());
+    }
+
+    #[test]
+    fn ack_all_and_recover_drop_prefetched_messages() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        use crate::consumer::Consumer;
+        use crate::options::{BasicAckOptions, BasicRecoverOptions};
+        use futures_lite::{
+            future::{block_on, poll_once},
+            StreamExt,
+        };
+
+        let (conn, channel, frames) = connected_channel_with_frames();
+        let mut consumer = Consumer::new(
+            "consumer-tag".into(),
+            Arc::new(async_global_executor_trait::AsyncGlobalExecutor),
+            None,
+            "consumed".into(),
+            BasicConsumeOptions::default(),
+            FieldTable::default(),
+        );
+        channel.register_consumer("consumer-tag".into(), consumer.clone());
+        let mut prefetched = || {
+            block_on(poll_once(consumer.next())).map(|delivery| {
+                delivery
+                    .expect("consumer got canceled")
+                    .unwrap()
+                    .delivery_tag
+            })
+        };
+
+        // Acking everything also covers the messages we didn't look at yet
+        deliver(&conn, &channel, "consumer-tag", 1, b"");
+        deliver(&conn, &channel, "consumer-tag", 2, b"");
+        assert_eq!(prefetched(), Some(1));
+        let _ = block_on(poll_once(
+            channel.basic_ack(0, BasicAckOptions { multiple: true }),
+        ));
+        assert_eq!(prefetched(), None);
+        assert_eq!(channel.in_flight(), 0);
+
+        // Recovering redelivers every unacked message with a new delivery tag
+        deliver(&conn, &channel, "consumer-tag", 3, b"");
+        deliver(&conn, &channel, "consumer-tag", 4, b"");
+        assert_eq!(prefetched(), Some(3));
+        let _ = block_on(poll_once(
+            channel.basic_recover(BasicRecoverOptions { requeue: true }),
+        ));
+        while frames.pop(true).is_some() {}
+        handle_frame(
+            &conn,
+            AMQPFrame::Method(
+                channel.id(),
+                AMQPClass::Basic(basic::AMQPMethod::RecoverOk(basic::RecoverOk {})),
+            ),
+        )
+        .unwrap();
+        assert_eq!(prefetched(), None);
+        assert_eq!(
+            block_on(channel.basic_ack(3, BasicAckOptions::default())),
+            Err(Error::UnknownDeliveryTag(3))
+        );
+        deliver(&conn, &channel, "consumer-tag", 5, b"");
+        deliver(&conn, &channel, "consumer-tag", 6, b"");
+        assert_eq!(prefetched(), Some(5));
+        assert_eq!(prefetched(), Some(6));
+        assert_eq!(channel.in_flight(), 2);
+    }
+
+    #[test]
+    fn deliveries_record_their_queue() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        use crate::options::BasicGetOptions;
+        use futures_lite::{
+            future::{block_on, poll_once},
+            StreamExt,
+        };
+
+        let (conn, frames) = running_connection();
+        let mut server = |frame: &AMQPFrame| match frame {
+            AMQPFrame::Method(id, AMQPClass::Basic(basic::AMQPMethod::Get(_))) => vec![
+                AMQPFrame::Method(
+                    *id,
+                    AMQPClass::Basic(basic::AMQPMethod::GetOk(basic::GetOk {
+                        delivery_tag: 2,
+                        redelivered: false,
+                        exchange: "billing".into(),
+                        routing_key: "invoices.eu".into(),
+                        message_count: 0,
+                    })),
+                ),
+                AMQPFrame::Header(
+                    *id,
+                    60,
+                    Box::new(AMQPContentHeader {
+                        class_id: 60,
+                        body_size: 0,
+                        properties: BasicProperties::default(),
+                    }),
+                ),
+            ],
+            frame => passive_declare_reply(frame, |method| match method {
+                AMQPClass::Basic(basic::AMQPMethod::Consume(consume)) => Some(AMQPClass::Basic(
+                    basic::AMQPMethod::ConsumeOk(basic::ConsumeOk {
+                        consumer_tag: consume.consumer_tag.clone(),
+                    }),
+                )),
+                _ => None,
+            })
+            .into_iter()
+            .collect(),
+        };
+        let channel =
+            run_against_server(&conn, &frames, conn.create_channel(), &mut server).unwrap();
+        let mut consumers = ["orders", "returns"].map(|queue| {
+            run_against_server(
+                &conn,
+                &frames,
+                channel.basic_consume(
+                    queue,
+                    queue,
+                    BasicConsumeOptions::default(),
+                    FieldTable::default(),
+                ),
+                &mut server,
+            )
+            .unwrap()
+        });
+        for frame in [
+            AMQPFrame::Method(
+                channel.id(),
+                AMQPClass::Basic(basic::AMQPMethod::Deliver(basic::Deliver {
+                    consumer_tag: "returns".into(),
+                    delivery_tag: 1,
+                    redelivered: false,
+                    exchange: "shop".into(),
+                    routing_key: "orders.eu".into(),
+                })),
+            ),
+            AMQPFrame::Header(
+                channel.id(),
+                60,
+                Box::new(AMQPContentHeader {
+                    class_id: 60,
+                    body_size: 0,
+                    properties: BasicProperties::default(),
+                }),
+            ),
+        ] {
+            handle_frame(&conn, frame).unwrap();
+        }
+
+        assert!(block_on(poll_once(consumers[0].next())).is_none());
+        let delivery = block_on(consumers[1].next()).unwrap().unwrap();
+        assert_eq!(delivery.queue.as_str(), "returns");
+        assert_eq!(delivery.routing_key.as_str(), "orders.eu");
+
+        let message = run_against_server(
+            &conn,
+            &frames,
+            channel.basic_get("invoices", BasicGetOptions::default()),
+            &mut server,
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(message.queue.as_str(), "invoices");
+        assert_eq!(message.routing_key.as_str(), "invoices.eu");
+    }
+
+    #[test]
+    fn basic_consume_many() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        use futures_lite::{future::block_on, StreamExt};
+
+        let (conn, frames) = running_connection();
+        let mut server = |frame: &AMQPFrame| {
+            passive_declare_reply(frame, |method| match method {
+                AMQPClass::Basic(basic::AMQPMethod::Consume(consume)) => Some(AMQPClass::Basic(
+                    basic::AMQPMethod::ConsumeOk(basic::ConsumeOk {
+                        consumer_tag: format!("amq.ctag-{}", consume.queue).into(),
+                    }),
+                )),
+                _ => None,
+            })
+        };
+
+        let channel =
+            run_against_server(&conn, &frames, conn.create_channel(), &mut server).unwrap();
+        let mut consumer = run_against_server(
+            &conn,
+            &frames,
+            conn.basic_consume_many(
+                channel.id(),
+                &["orders".into(), "invoices".into()],
+                BasicConsumeOptions::default(),
+            ),
+            &mut server,
+        )
+        .unwrap();
+        assert_eq!(
+            consumer
+                .consumers()
+                .iter()
+                .map(|consumer| (consumer.queue(), consumer.tag()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("orders".into(), "amq.ctag-orders".into()),
+                ("invoices".into(), "amq.ctag-invoices".into()),
+            ]
+        );
+
+        for (delivery_tag, consumer_tag) in [(1, "amq.ctag-invoices"), (2, "amq.ctag-orders")] {
+            deliver(&conn, &channel, consumer_tag, delivery_tag, b"");
+        }
+
+        let mut received = (0..2)
+            .map(|_| {
+                let delivery = block_on(consumer.next()).unwrap().unwrap();
+                (delivery.delivery_tag, delivery.queue)
+            })
+            .collect::<Vec<_>>();
+        received.sort();
+        assert_eq!(received, vec![(1, "invoices".into()), (2, "orders".into())]);
+        assert!(matches!(
+            block_on(conn.basic_consume_many(42, &[], BasicConsumeOptions::default())),
+            Err(Error::InvalidChannel(42))
+        ));
+    }
+
+    #[test]
+    fn basic_cancel_all() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        use crate::consumer::Consumer;
+        use crate::options::BasicCancelOptions;
+
+        let (conn, channel, frames) = connected_channel_with_frames();
+        for consumer_tag in ["first", "second", "canceling"] {
+            let consumer = Consumer::new(
+                consumer_tag.into(),
+                Arc::new(async_global_executor_trait::AsyncGlobalExecutor),
+                None,
+                "queue".into(),
+                BasicConsumeOptions::default(),
+                FieldTable::default(),
+            );
+            if consumer_tag == "canceling" {
+                consumer.start_cancel();
+            }
+            channel.register_consumer(consumer_tag.into(), consumer);
+        }
+
+        let mut canceled = Vec::new();
+        run_against_server(
+            &conn,
+            &frames,
+            channel.basic_cancel_all(BasicCancelOptions::default()),
+            |frame| match frame {
+                AMQPFrame::Method(id, AMQPClass::Basic(basic::AMQPMethod::Cancel(cancel))) => {
+                    canceled.push(cancel.consumer_tag.to_string());
+                    Some(AMQPFrame::Method(
+                        *id,
+                        AMQPClass::Basic(basic::AMQPMethod::CancelOk(basic::CancelOk {
+                            consumer_tag: cancel.consumer_tag.clone(),
+                        })),
+                    ))
+                }
+                _ => None,
+            },
+        )
+        .unwrap();
+        canceled.sort();
+        assert_eq!(canceled, ["first", "second"]);
+
+        // Nothing left to cancel
+        run_against_server(
+            &conn,
+            &frames,
+            channel.basic_cancel_all(BasicCancelOptions::default()),
+            |_| None,
+        )
+        .unwrap();
+        assert!(!frames.has_pending());
+    }
 }
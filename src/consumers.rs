@@ -3,7 +3,7 @@ use crate::{
     error_holder::ErrorHolder,
     message::Delivery,
     topology_internal::ConsumerDefinitionInternal,
-    types::{PayloadSize, ShortString},
+    types::{DeliveryTag, PayloadSize, ShortString},
     BasicProperties, Error,
 };
 use parking_lot::Mutex;
@@ -35,7 +35,23 @@ impl Consumers {
         }
     }
 
-    pub(crate) fn start_delivery<S: Hash + Eq + ?Sized, F: FnOnce(ErrorHolder) -> Delivery>(
+    pub(crate) fn contains<S: Hash + Eq + ?Sized>(&self, consumer_tag: &S) -> bool
+    where
+        ShortString: Borrow<S>,
+    {
+        self.0.lock().contains_key(consumer_tag)
+    }
+
+    pub(crate) fn active_tags(&self) -> Vec<ShortString> {
+        self.0
+            .lock()
+            .iter()
+            .filter(|(_, consumer)| consumer.state().is_active())
+            .map(|(tag, _)| tag.clone())
+            .collect()
+    }
+
+    pub(crate) fn start_delivery<S: Hash + Eq + ?Sized, F: FnOnce(ErrorHolder, bool) -> Delivery>(
         &self,
         consumer_tag: &S,
         message: F,
@@ -43,7 +59,7 @@ impl Consumers {
         ShortString: Borrow<S>,
     {
         if let Some(consumer) = self.0.lock().get_mut(consumer_tag) {
-            consumer.start_new_delivery(message(consumer.error()));
+            consumer.start_new_delivery(message(consumer.error(), consumer.options().no_ack));
         }
     }
 
@@ -79,6 +95,18 @@ impl Consumers {
         }
     }
 
+    pub(crate) fn drop_prefetched_messages_before(&self, delivery_tag: DeliveryTag) {
+        for consumer in self.0.lock().values() {
+            consumer.drop_prefetched_messages_before(delivery_tag);
+        }
+    }
+
+    pub(crate) fn reset(&self) {
+        for consumer in self.0.lock().values() {
+            consumer.reset();
+        }
+    }
+
     pub(crate) fn start_cancel(&self) {
         for consumer in self.0.lock().values() {
             consumer.start_cancel();
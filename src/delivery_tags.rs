@@ -0,0 +1,513 @@
+use crate::{
+    types::{DeliveryTag, ShortString},
+    Error, Result,
+};
+use parking_lot::Mutex;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+    sync::Arc,
+};
+
+/// The delivery tags handed to us by the server which still need to be acked, nacked or rejected,
+/// along with the tag of the consumer they were delivered to (`None` for `basic_get`).
+#[derive(Clone, Default)]
+pub(crate) struct DeliveryTags(Arc<Mutex<BTreeMap<DeliveryTag, Option<ShortString>>>>);
+
+impl DeliveryTags {
+    pub(crate) fn register(&self, delivery_tag: DeliveryTag, consumer_tag: Option<ShortString>) {
+        self.0.lock().insert(delivery_tag, consumer_tag);
+    }
+
+    // The server closes the channel with PRECONDITION_FAILED when we settle an unknown delivery
+    // tag, even with multiple, so only accept outstanding ones (or 0 + multiple for everything)
+    pub(crate) fn check(&self, delivery_tag: DeliveryTag, multiple: bool) -> Result<()> {
+        if (multiple && delivery_tag == 0) || self.0.lock().contains_key(&delivery_tag) {
+            Ok(())
+        } else {
+            Err(Error::UnknownDeliveryTag(delivery_tag))
+        }
+    }
+
+    pub(crate) fn settle(&self, delivery_tag: DeliveryTag, multiple: bool) {
+        let mut tags = self.0.lock();
+        if !multiple {
+            tags.remove(&delivery_tag);
+        } else if delivery_tag == 0 {
+            tags.clear();
+        } else {
+            tags.retain(|tag, _| *tag > delivery_tag);
+        }
+    }
+
+    // Split the tags to settle into the last one of the longest run of our oldest outstanding
+    // tags, which can be settled at once with multiple, and the ones to settle one by one
+    pub(crate) fn coalesce(
+        &self,
+        delivery_tags: &[DeliveryTag],
+    ) -> Result<(Option<DeliveryTag>, Vec<DeliveryTag>)> {
+        let tags = self.0.lock();
+        let requested = delivery_tags.iter().copied().collect::<BTreeSet<_>>();
+        if let Some(unknown) = requested.iter().find(|tag| !tags.contains_key(tag)) {
+            return Err(Error::UnknownDeliveryTag(*unknown));
+        }
+        let run_end = tags
+            .keys()
+            .take_while(|tag| requested.contains(tag))
+            .last()
+            .copied();
+        let others = requested
+            .into_iter()
+            .filter(|tag| run_end.map_or(true, |run_end| *tag > run_end))
+            .collect();
+        Ok((run_end, others))
+    }
+
+    pub(crate) fn outstanding(&self) -> Vec<DeliveryTag> {
+        self.0.lock().keys().copied().collect()
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.0.lock().len()
+    }
+
+    pub(crate) fn delivered_to(&self, consumer_tag: &str) -> usize {
+        self.0
+            .lock()
+            .values()
+            .filter(|tag| tag.as_ref().is_some_and(|tag| tag.as_str() == consumer_tag))
+            .count()
+    }
+
+    pub(crate) fn clear(&self) {
+        self.0.lock().clear();
+    }
+}
+
+impl fmt::Debug for DeliveryTags {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug = f.debug_tuple("DeliveryTags");
+        if let Some(tags) = self.0.try_lock() {
+            debug.field(&*tags);
+        }
+        debug.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::connection::test_utils::*;
+    use crate::options::BasicConsumeOptions;
+    use crate::types::FieldTable;
+    use amq_protocol::frame::AMQPFrame;
+    use amq_protocol::protocol::{basic, AMQPClass};
+
+    #[test]
+    fn settle_outstanding_tags() {
+        let tags = DeliveryTags::default();
+        for tag in 1..=5 {
+            tags.register(tag, None);
+        }
+        assert_eq!(tags.check(2, false), Ok(()));
+        tags.settle(2, false);
+        assert_eq!(tags.check(2, false), Err(Error::UnknownDeliveryTag(2)));
+        assert_eq!(tags.check(4, true), Ok(()));
+        tags.settle(4, true);
+        assert_eq!(tags.check(3, false), Err(Error::UnknownDeliveryTag(3)));
+        assert_eq!(tags.check(6, true), Err(Error::UnknownDeliveryTag(6)));
+        assert_eq!(tags.check(0, true), Ok(()));
+        tags.settle(0, true);
+        assert_eq!(tags.check(5, false), Err(Error::UnknownDeliveryTag(5)));
+    }
+
+    #[test]
+    fn coalesce_contiguous_tags() {
+        let tags = DeliveryTags::default();
+        for tag in 3..=8 {
+            tags.register(tag, None);
+        }
+        assert_eq!(tags.coalesce(&[5, 3, 4]), Ok((Some(5), Vec::new())));
+        assert_eq!(tags.coalesce(&[3, 4, 6, 8]), Ok((Some(4), vec![6, 8])));
+        assert_eq!(tags.coalesce(&[5, 6]), Ok((None, vec![5, 6])));
+        assert_eq!(tags.coalesce(&[]), Ok((None, Vec::new())));
+        assert_eq!(tags.coalesce(&[3, 9]), Err(Error::UnknownDeliveryTag(9)));
+    }
+
+    #[test]
+    fn multiple_ack_settles_up_to_its_tag() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        use crate::options::BasicAckOptions;
+        use futures_lite::{
+            future::{block_on, poll_once},
+            StreamExt,
+        };
+
+        let (conn, frames) = running_connection();
+        let mut server = |frame: &AMQPFrame| {
+            passive_declare_reply(frame, |method| match method {
+                AMQPClass::Basic(basic::AMQPMethod::Consume(consume)) => Some(AMQPClass::Basic(
+                    basic::AMQPMethod::ConsumeOk(basic::ConsumeOk {
+                        consumer_tag: consume.consumer_tag.clone(),
+                    }),
+                )),
+                _ => None,
+            })
+        };
+        let channel =
+            run_against_server(&conn, &frames, conn.create_channel(), &mut server).unwrap();
+        let mut consumer = run_against_server(
+            &conn,
+            &frames,
+            channel.basic_consume(
+                "queue",
+                "consumer",
+                BasicConsumeOptions::default(),
+                FieldTable::default(),
+            ),
+            &mut server,
+        )
+        .unwrap();
+        for delivery_tag in 1..=5 {
+            deliver(&conn, &channel, "consumer", delivery_tag, b"");
+        }
+        assert_eq!(
+            conn.unacked_deliveries(channel.id()).unwrap(),
+            vec![1, 2, 3, 4, 5]
+        );
+
+        run_against_server(
+            &conn,
+            &frames,
+            channel.basic_ack(3, BasicAckOptions { multiple: true }),
+            &mut server,
+        )
+        .unwrap();
+        assert_eq!(conn.unacked_deliveries(channel.id()).unwrap(), vec![4, 5]);
+        assert_eq!(consumer.in_flight(), 2);
+        for delivery_tag in [4, 5] {
+            let delivery = block_on(consumer.next()).unwrap().unwrap();
+            assert_eq!(delivery.delivery_tag, delivery_tag);
+        }
+        assert!(block_on(poll_once(consumer.next())).is_none());
+        assert_eq!(
+            block_on(channel.basic_ack(2, BasicAckOptions { multiple: true })),
+            Err(Error::UnknownDeliveryTag(2))
+        );
+    }
+
+    #[test]
+    fn unknown_delivery_tags_are_refused() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        use crate::consumer::Consumer;
+        use crate::options::{BasicAckOptions, BasicNackOptions, BasicRejectOptions};
+        use futures_lite::future::{block_on, poll_once};
+
+        let (conn, channel, frames) = connected_channel_with_frames();
+        for (consumer_tag, no_ack) in [("acked", false), ("no-ack", true)] {
+            channel.register_consumer(
+                consumer_tag.into(),
+                Consumer::new(
+                    consumer_tag.into(),
+                    Arc::new(async_global_executor_trait::AsyncGlobalExecutor),
+                    None,
+                    "consumed".into(),
+                    BasicConsumeOptions {
+                        no_ack,
+                        ..Default::default()
+                    },
+                    FieldTable::default(),
+                ),
+            );
+        }
+        for (delivery_tag, consumer_tag) in [(1, "acked"), (2, "no-ack"), (3, "acked")] {
+            deliver(&conn, &channel, consumer_tag, delivery_tag, b"");
+        }
+
+        // Never delivered, and delivered to a no_ack consumer
+        assert_eq!(
+            block_on(channel.basic_ack(42, BasicAckOptions::default())),
+            Err(Error::UnknownDeliveryTag(42))
+        );
+        assert_eq!(
+            block_on(channel.basic_ack(2, BasicAckOptions { multiple: true })),
+            Err(Error::UnknownDeliveryTag(2))
+        );
+        assert!(frames.pop(true).is_none());
+
+        let _ = block_on(poll_once(channel.basic_ack(1, BasicAckOptions::default())));
+        assert!(frames.pop(true).is_some());
+        // Already acked
+        assert_eq!(
+            block_on(channel.basic_reject(1, BasicRejectOptions::default())),
+            Err(Error::UnknownDeliveryTag(1))
+        );
+        assert!(frames.pop(true).is_none());
+
+        let _ = block_on(poll_once(channel.basic_nack(
+            0,
+            BasicNackOptions {
+                multiple: true,
+                requeue: true,
+            },
+        )));
+        assert!(frames.pop(true).is_some());
+        assert_eq!(
+            block_on(channel.basic_nack(3, BasicNackOptions::default())),
+            Err(Error::UnknownDeliveryTag(3))
+        );
+        assert!(frames.pop(true).is_none());
+    }
+
+    #[test]
+    fn failed_ack_can_be_retried() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        use crate::channel_status::ChannelState;
+        use crate::consumer::Consumer;
+        use crate::options::BasicAckOptions;
+        use futures_lite::future::{block_on, poll_once};
+
+        let (conn, channel, frames) = connected_channel_with_frames();
+        channel.register_consumer(
+            "consumer".into(),
+            Consumer::new(
+                "consumer".into(),
+                Arc::new(async_global_executor_trait::AsyncGlobalExecutor),
+                None,
+                "consumed".into(),
+                BasicConsumeOptions::default(),
+                FieldTable::default(),
+            ),
+        );
+        deliver(&conn, &channel, "consumer", 1, b"");
+
+        channel.set_state(ChannelState::Closing);
+        assert_eq!(
+            block_on(channel.basic_ack(1, BasicAckOptions::default())),
+            Err(Error::InvalidChannelState(ChannelState::Closing))
+        );
+        assert_eq!(conn.unacked_deliveries(channel.id()).unwrap(), vec![1]);
+
+        channel.set_state(ChannelState::Connected);
+        let _ = block_on(poll_once(channel.basic_ack(1, BasicAckOptions::default())));
+        assert!(frames.pop(true).is_some());
+        assert!(conn.unacked_deliveries(channel.id()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn unacked_deliveries() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        use crate::options::{BasicAckOptions, BasicGetOptions};
+        use crate::BasicProperties;
+        use amq_protocol::frame::AMQPContentHeader;
+
+        let (conn, frames) = running_connection();
+        let mut server = |frame: &AMQPFrame| match frame {
+            AMQPFrame::Method(id, AMQPClass::Basic(basic::AMQPMethod::Get(_))) => vec![
+                AMQPFrame::Method(
+                    *id,
+                    AMQPClass::Basic(basic::AMQPMethod::GetOk(basic::GetOk {
+                        delivery_tag: 4,
+                        redelivered: false,
+                        exchange: "".into(),
+                        routing_key: "queue".into(),
+                        message_count: 0,
+                    })),
+                ),
+                AMQPFrame::Header(
+                    *id,
+                    60,
+                    Box::new(AMQPContentHeader {
+                        class_id: 60,
+                        body_size: 0,
+                        properties: BasicProperties::default(),
+                    }),
+                ),
+            ],
+            frame => passive_declare_reply(frame, |method| match method {
+                AMQPClass::Basic(basic::AMQPMethod::Consume(consume)) => Some(AMQPClass::Basic(
+                    basic::AMQPMethod::ConsumeOk(basic::ConsumeOk {
+                        consumer_tag: consume.consumer_tag.clone(),
+                    }),
+                )),
+                _ => None,
+            })
+            .into_iter()
+            .collect(),
+        };
+        let channel =
+            run_against_server(&conn, &frames, conn.create_channel(), &mut server).unwrap();
+        let consumer = run_against_server(
+            &conn,
+            &frames,
+            channel.basic_consume(
+                "queue",
+                "audited",
+                BasicConsumeOptions::default(),
+                FieldTable::default(),
+            ),
+            &mut server,
+        )
+        .unwrap();
+        assert!(conn.unacked_deliveries(channel.id()).unwrap().is_empty());
+        for delivery_tag in 1..=3 {
+            deliver(&conn, &channel, "audited", delivery_tag, b"");
+        }
+        assert_eq!(
+            conn.unacked_deliveries(channel.id()).unwrap(),
+            vec![1, 2, 3]
+        );
+
+        run_against_server(
+            &conn,
+            &frames,
+            channel.basic_ack(2, BasicAckOptions::default()),
+            &mut server,
+        )
+        .unwrap();
+        assert_eq!(conn.unacked_deliveries(channel.id()).unwrap(), vec![1, 3]);
+        assert_eq!(conn.unacked_deliveries(42), Err(Error::InvalidChannel(42)));
+
+        // Fetched messages are tracked along with the consumed ones, but don't belong to any
+        // consumer
+        run_against_server(
+            &conn,
+            &frames,
+            channel.basic_get("queue", BasicGetOptions::default()),
+            &mut server,
+        )
+        .unwrap();
+        assert_eq!(
+            conn.unacked_deliveries(channel.id()).unwrap(),
+            vec![1, 3, 4]
+        );
+        assert_eq!(channel.in_flight(), 3);
+        assert_eq!(consumer.in_flight(), 2);
+    }
+
+    #[test]
+    fn basic_ack_many() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        let (conn, frames) = running_connection();
+        let mut acks = Vec::new();
+        let mut server = |frame: &AMQPFrame| match frame {
+            AMQPFrame::Method(_, AMQPClass::Basic(basic::AMQPMethod::Ack(ack))) => {
+                acks.push((ack.delivery_tag, ack.multiple));
+                None
+            }
+            frame => passive_declare_reply(frame, |method| match method {
+                AMQPClass::Basic(basic::AMQPMethod::Consume(consume)) => Some(AMQPClass::Basic(
+                    basic::AMQPMethod::ConsumeOk(basic::ConsumeOk {
+                        consumer_tag: consume.consumer_tag.clone(),
+                    }),
+                )),
+                _ => None,
+            }),
+        };
+        let channel =
+            run_against_server(&conn, &frames, conn.create_channel(), &mut server).unwrap();
+        run_against_server(
+            &conn,
+            &frames,
+            channel.basic_consume(
+                "queue",
+                "batch",
+                BasicConsumeOptions::default(),
+                FieldTable::default(),
+            ),
+            &mut server,
+        )
+        .unwrap();
+        for delivery_tag in 1..=6 {
+            deliver(&conn, &channel, "batch", delivery_tag, b"");
+        }
+
+        // Contiguous
+        let sent = run_against_server(
+            &conn,
+            &frames,
+            conn.basic_ack_many(channel.id(), &[2, 1, 3]),
+            &mut server,
+        );
+        assert_eq!(sent, Ok(1));
+        // With a gap
+        let sent = run_against_server(
+            &conn,
+            &frames,
+            conn.basic_ack_many(channel.id(), &[4, 6]),
+            &mut server,
+        );
+        assert_eq!(sent, Ok(2));
+        assert_eq!(
+            run_against_server(
+                &conn,
+                &frames,
+                conn.basic_ack_many(channel.id(), &[5, 6]),
+                &mut server,
+            ),
+            Err(Error::UnknownDeliveryTag(6))
+        );
+        assert_eq!(acks, vec![(3, true), (4, true), (6, false)]);
+    }
+
+    #[test]
+    fn no_ack_deliveries_have_a_noop_acker() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        use crate::options::BasicAckOptions;
+        use futures_lite::{future::block_on, StreamExt};
+
+        let (conn, frames) = running_connection();
+        let mut acks = 0;
+        let mut server = |frame: &AMQPFrame| match frame {
+            AMQPFrame::Method(_, AMQPClass::Basic(basic::AMQPMethod::Ack(_))) => {
+                acks += 1;
+                None
+            }
+            frame => passive_declare_reply(frame, |method| match method {
+                AMQPClass::Basic(basic::AMQPMethod::Consume(consume)) => Some(AMQPClass::Basic(
+                    basic::AMQPMethod::ConsumeOk(basic::ConsumeOk {
+                        consumer_tag: consume.consumer_tag.clone(),
+                    }),
+                )),
+                _ => None,
+            }),
+        };
+        let channel =
+            run_against_server(&conn, &frames, conn.create_channel(), &mut server).unwrap();
+        let mut consumer = run_against_server(
+            &conn,
+            &frames,
+            channel.basic_consume(
+                "queue",
+                "fire-and-forget",
+                BasicConsumeOptions {
+                    no_ack: true,
+                    ..Default::default()
+                },
+                FieldTable::default(),
+            ),
+            &mut server,
+        )
+        .unwrap();
+        deliver(&conn, &channel, "fire-and-forget", 1, b"");
+        let delivery = block_on(consumer.next()).unwrap().unwrap();
+
+        assert_eq!(
+            run_against_server(
+                &conn,
+                &frames,
+                delivery.ack(BasicAckOptions::default()),
+                &mut server,
+            ),
+            Ok(())
+        );
+        assert_eq!(acks, 0);
+        assert!(conn.unacked_deliveries(channel.id()).unwrap().is_empty());
+    }
+}
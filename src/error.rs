@@ -1,9 +1,12 @@
 use crate::{
-    channel_status::ChannelState, connection_status::ConnectionState, protocol::AMQPError,
-    types::ChannelId,
+    auth::SASLMechanism,
+    channel_status::ChannelState,
+    connection_status::ConnectionState,
+    protocol::AMQPError,
+    types::{ChannelId, DeliveryTag, ShortString},
 };
 use amq_protocol::frame::{GenError, ParserError, ProtocolVersion};
-use std::{error, fmt, io, sync::Arc};
+use std::{error, fmt, io, sync::Arc, time::Duration};
 
 /// A std Result with a lapin::Error error type
 pub type Result<T> = std::result::Result<T, Error>;
@@ -21,13 +24,27 @@ pub enum Error {
     InvalidChannel(ChannelId),
     InvalidChannelState(ChannelState),
     InvalidConnectionState(ConnectionState),
+    InvalidUri(String),
+    InvalidQueueArguments(String),
+    UnsupportedSASLMechanism(SASLMechanism),
+    UnknownDeliveryTag(DeliveryTag),
+    UnboundAcker,
+    UnsupportedByServer(&'static str),
 
     IOError(Arc<io::Error>),
     ParsingError(ParserError),
     ProtocolError(AMQPError),
+    ExclusiveConsumeConflict(AMQPError),
+    DuplicateConsumerTag(ShortString),
     SerialisationError(Arc<GenError>),
+    UnknownContentType(Option<ShortString>),
+    DecodingError(Arc<dyn error::Error + Send + Sync>),
 
     MissingHeartbeatError,
+    ReadTimeout(Duration),
+    RequestTimeout(Duration),
+    ConfirmTimeout(usize),
+    ConfirmWindowFull(usize),
 }
 
 impl Error {
@@ -64,15 +81,65 @@ impl fmt::Display for Error {
             Error::InvalidConnectionState(state) => {
                 write!(f, "invalid connection state: {:?}", state)
             }
+            Error::InvalidUri(err) => write!(f, "invalid AMQP URI: {}", err),
+            Error::InvalidQueueArguments(err) => write!(f, "invalid queue arguments: {}", err),
+            Error::UnsupportedSASLMechanism(mechanism) => {
+                write!(
+                    f,
+                    "the server doesn't support the {} SASL mechanism",
+                    mechanism
+                )
+            }
+            Error::UnknownDeliveryTag(delivery_tag) => {
+                write!(f, "unknown delivery tag: {}", delivery_tag)
+            }
+            Error::UnboundAcker => write!(
+                f,
+                "the message wasn't delivered to us and cannot be acked, nacked nor rejected"
+            ),
+            Error::UnsupportedByServer(capability) => {
+                write!(
+                    f,
+                    "the server doesn't advertise the {} capability",
+                    capability
+                )
+            }
 
             Error::IOError(e) => write!(f, "IO error: {}", e),
             Error::ParsingError(e) => write!(f, "failed to parse: {}", e),
             Error::ProtocolError(e) => write!(f, "protocol error: {}", e),
+            Error::ExclusiveConsumeConflict(e) => {
+                write!(f, "exclusive access to the queue was refused: {}", e)
+            }
+            Error::DuplicateConsumerTag(consumer_tag) => write!(
+                f,
+                "a consumer with the tag {} already exists on this channel",
+                consumer_tag
+            ),
             Error::SerialisationError(e) => write!(f, "failed to serialise: {}", e),
+            Error::UnknownContentType(Some(content_type)) => {
+                write!(f, "don't know how to decode {} payloads", content_type)
+            }
+            Error::UnknownContentType(None) => {
+                write!(f, "cannot decode a payload without content type")
+            }
+            Error::DecodingError(e) => write!(f, "failed to decode payload: {}", e),
 
             Error::MissingHeartbeatError => {
                 write!(f, "no heartbeat received from server for too long")
             }
+            Error::ReadTimeout(timeout) => {
+                write!(f, "nothing received from server for {:?}", timeout)
+            }
+            Error::RequestTimeout(timeout) => {
+                write!(f, "no answer received from server after {:?}", timeout)
+            }
+            Error::ConfirmTimeout(unconfirmed) => {
+                write!(f, "{} publisher confirms were still pending", unconfirmed)
+            }
+            Error::ConfirmWindowFull(max) => {
+                write!(f, "{} publisher confirms are already pending", max)
+            }
         }
     }
 }
@@ -83,7 +150,9 @@ impl error::Error for Error {
             Error::IOError(e) => Some(&**e),
             Error::ParsingError(e) => Some(e),
             Error::ProtocolError(e) => Some(e),
+            Error::ExclusiveConsumeConflict(e) => Some(e),
             Error::SerialisationError(e) => Some(&**e),
+            Error::DecodingError(e) => Some(&**e),
             _ => None,
         }
     }
@@ -113,6 +182,20 @@ impl PartialEq for Error {
             (InvalidConnectionState(left_inner), InvalidConnectionState(right_inner)) => {
                 left_inner == right_inner
             }
+            (InvalidUri(left_inner), InvalidUri(right_inner)) => left_inner == right_inner,
+            (InvalidQueueArguments(left_inner), InvalidQueueArguments(right_inner)) => {
+                left_inner == right_inner
+            }
+            (UnsupportedSASLMechanism(left_inner), UnsupportedSASLMechanism(right_inner)) => {
+                left_inner == right_inner
+            }
+            (UnknownDeliveryTag(left_inner), UnknownDeliveryTag(right_inner)) => {
+                left_inner == right_inner
+            }
+            (UnboundAcker, UnboundAcker) => true,
+            (UnsupportedByServer(left_inner), UnsupportedByServer(right_inner)) => {
+                left_inner == right_inner
+            }
 
             (IOError(_), IOError(_)) => {
                 error!("Unable to compare lapin::Error::IOError");
@@ -120,10 +203,30 @@ impl PartialEq for Error {
             }
             (ParsingError(left_inner), ParsingError(right_inner)) => left_inner == right_inner,
             (ProtocolError(left_inner), ProtocolError(right_inner)) => left_inner == right_inner,
+            (ExclusiveConsumeConflict(left_inner), ExclusiveConsumeConflict(right_inner)) => {
+                left_inner == right_inner
+            }
+            (DuplicateConsumerTag(left_inner), DuplicateConsumerTag(right_inner)) => {
+                left_inner == right_inner
+            }
             (SerialisationError(_), SerialisationError(_)) => {
                 error!("Unable to compare lapin::Error::SerialisationError");
                 false
             }
+            (UnknownContentType(left_inner), UnknownContentType(right_inner)) => {
+                left_inner == right_inner
+            }
+            (DecodingError(_), DecodingError(_)) => {
+                error!("Unable to compare lapin::Error::DecodingError");
+                false
+            }
+
+            (ReadTimeout(left_inner), ReadTimeout(right_inner)) => left_inner == right_inner,
+            (RequestTimeout(left_inner), RequestTimeout(right_inner)) => left_inner == right_inner,
+            (ConfirmTimeout(left_inner), ConfirmTimeout(right_inner)) => left_inner == right_inner,
+            (ConfirmWindowFull(left_inner), ConfirmWindowFull(right_inner)) => {
+                left_inner == right_inner
+            }
 
             _ => false,
         }
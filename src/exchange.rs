@@ -1,3 +1,7 @@
+use crate::{
+    options::ExchangeDeclareOptions,
+    types::{AMQPValue, FieldTable, LongString, ShortString},
+};
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
@@ -26,3 +30,245 @@ impl ExchangeKind {
         }
     }
 }
+
+/// Arguments to bind a queue or an exchange to a headers exchange.
+///
+/// The binding either matches when all the headers match ([`HeadersBinding::all`])
+/// or when any of them does ([`HeadersBinding::any`]). The `x-match` argument is
+/// handled for you and cannot be overridden through [`HeadersBinding::header`].
+///
+/// ```rust
+/// use lapin::{
+///     types::{FieldTable, LongString},
+///     HeadersBinding,
+/// };
+///
+/// let arguments: FieldTable = HeadersBinding::all([
+///     ("format", LongString::from("pdf")),
+///     ("type", LongString::from("report")),
+/// ])
+/// .into();
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct HeadersBinding {
+    x_match: &'static str,
+    headers: FieldTable,
+}
+
+impl HeadersBinding {
+    /// Match messages carrying all of the given headers.
+    pub fn all<K: Into<ShortString>, V: Into<AMQPValue>, I: IntoIterator<Item = (K, V)>>(
+        headers: I,
+    ) -> Self {
+        Self::new("all", headers)
+    }
+
+    /// Match messages carrying any of the given headers.
+    pub fn any<K: Into<ShortString>, V: Into<AMQPValue>, I: IntoIterator<Item = (K, V)>>(
+        headers: I,
+    ) -> Self {
+        Self::new("any", headers)
+    }
+
+    fn new<K: Into<ShortString>, V: Into<AMQPValue>, I: IntoIterator<Item = (K, V)>>(
+        x_match: &'static str,
+        headers: I,
+    ) -> Self {
+        headers.into_iter().fold(
+            Self {
+                x_match,
+                headers: FieldTable::default(),
+            },
+            |binding, (key, value)| binding.header(key, value),
+        )
+    }
+
+    /// Add a header to match.
+    #[must_use]
+    pub fn header<K: Into<ShortString>, V: Into<AMQPValue>>(mut self, key: K, value: V) -> Self {
+        self.headers.insert(key.into(), value.into());
+        self
+    }
+
+    /// Get the arguments to pass to `queue_bind` or `exchange_bind`.
+    pub fn into_field_table(self) -> FieldTable {
+        let mut arguments = self.headers;
+        arguments.insert(
+            "x-match".into(),
+            AMQPValue::LongString(LongString::from(self.x_match)),
+        );
+        arguments
+    }
+}
+
+impl From<HeadersBinding> for FieldTable {
+    fn from(binding: HeadersBinding) -> Self {
+        binding.into_field_table()
+    }
+}
+
+/// Typed builder for the `exchange_declare` arguments.
+///
+/// ```rust
+/// use lapin::{types::FieldTable, ExchangeArguments};
+///
+/// let arguments: FieldTable = ExchangeArguments::default()
+///     .alternate_exchange("unroutable")
+///     .into();
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ExchangeArguments(FieldTable);
+
+impl ExchangeArguments {
+    /// Route the messages which can't be routed by this exchange to this other one
+    /// (`alternate-exchange`).
+    #[must_use]
+    pub fn alternate_exchange(mut self, exchange: &str) -> Self {
+        self.0.insert(
+            "alternate-exchange".into(),
+            AMQPValue::LongString(LongString::from(exchange)),
+        );
+        self
+    }
+
+    /// Get the arguments to pass to `exchange_declare`.
+    pub fn into_field_table(self) -> FieldTable {
+        self.0
+    }
+}
+
+impl From<ExchangeArguments> for FieldTable {
+    fn from(arguments: ExchangeArguments) -> Self {
+        arguments.into_field_table()
+    }
+}
+
+/// Builder for the parameters of `exchange_declare`.
+///
+/// Every flag defaults to `false` and there are no arguments unless specified.
+///
+/// ```rust
+/// use lapin::{ExchangeArguments, ExchangeDeclare, ExchangeKind};
+///
+/// let declare = ExchangeDeclare::new("logs", ExchangeKind::Topic)
+///     .durable()
+///     .arguments(ExchangeArguments::default().alternate_exchange("unroutable"));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ExchangeDeclare {
+    pub(crate) exchange: ShortString,
+    pub(crate) kind: ExchangeKind,
+    pub(crate) options: ExchangeDeclareOptions,
+    pub(crate) arguments: FieldTable,
+}
+
+impl ExchangeDeclare {
+    pub fn new(exchange: &str, kind: ExchangeKind) -> Self {
+        Self {
+            exchange: exchange.into(),
+            kind,
+            ..Default::default()
+        }
+    }
+
+    /// Only check whether the exchange exists, without creating it.
+    #[must_use]
+    pub fn passive(mut self) -> Self {
+        self.options.passive = true;
+        self
+    }
+
+    /// Make the exchange survive a broker restart.
+    #[must_use]
+    pub fn durable(mut self) -> Self {
+        self.options.durable = true;
+        self
+    }
+
+    /// Delete the exchange once it's no longer bound to anything.
+    #[must_use]
+    pub fn auto_delete(mut self) -> Self {
+        self.options.auto_delete = true;
+        self
+    }
+
+    /// Forbid publishing directly to the exchange, it can only be bound to other exchanges.
+    #[must_use]
+    pub fn internal(mut self) -> Self {
+        self.options.internal = true;
+        self
+    }
+
+    /// Don't wait for the server to confirm the declaration.
+    #[must_use]
+    pub fn nowait(mut self) -> Self {
+        self.options.nowait = true;
+        self
+    }
+
+    #[must_use]
+    pub fn arguments<A: Into<FieldTable>>(mut self, arguments: A) -> Self {
+        self.arguments = arguments.into();
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn headers_binding_all() {
+        let arguments = HeadersBinding::all([
+            ("format", LongString::from("pdf")),
+            ("type", LongString::from("report")),
+        ])
+        .header("x-match", LongString::from("any"))
+        .into_field_table();
+        let arguments = arguments.inner();
+        assert_eq!(arguments.len(), 3);
+        assert_eq!(
+            arguments.get("x-match"),
+            Some(&AMQPValue::LongString("all".into()))
+        );
+        assert_eq!(
+            arguments.get("format"),
+            Some(&AMQPValue::LongString("pdf".into()))
+        );
+        assert_eq!(
+            arguments.get("type"),
+            Some(&AMQPValue::LongString("report".into()))
+        );
+    }
+
+    #[test]
+    fn headers_binding_any() {
+        let arguments: FieldTable = HeadersBinding::any([("priority", 5u8)])
+            .header("urgent", true)
+            .into();
+        let arguments = arguments.inner();
+        assert_eq!(arguments.len(), 3);
+        assert_eq!(
+            arguments.get("x-match"),
+            Some(&AMQPValue::LongString("any".into()))
+        );
+        assert_eq!(
+            arguments.get("priority"),
+            Some(&AMQPValue::ShortShortUInt(5))
+        );
+        assert_eq!(arguments.get("urgent"), Some(&AMQPValue::Boolean(true)));
+    }
+
+    #[test]
+    fn exchange_arguments() {
+        let arguments: FieldTable = ExchangeArguments::default()
+            .alternate_exchange("unroutable")
+            .into();
+        let arguments = arguments.inner();
+        assert_eq!(arguments.len(), 1);
+        assert_eq!(
+            arguments.get("alternate-exchange"),
+            Some(&AMQPValue::LongString("unroutable".into()))
+        );
+    }
+}
@@ -0,0 +1,129 @@
+use crate::protocol::AMQPClass;
+use parking_lot::RwLock;
+use std::{fmt, sync::Arc};
+
+/// Whether a method frame was received from or sent to the server.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    Inbound,
+    Outbound,
+}
+
+type ObserverFn = Arc<dyn Fn(Direction, &AMQPClass) + Send + Sync + 'static>;
+
+#[derive(Clone, Default)]
+pub(crate) struct FrameObserver(Arc<RwLock<Option<ObserverFn>>>);
+
+impl FrameObserver {
+    pub(crate) fn set_observer<O: Fn(Direction, &AMQPClass) + Send + Sync + 'static>(
+        &self,
+        observer: O,
+    ) {
+        *self.0.write() = Some(Arc::new(observer));
+    }
+
+    // Don't call the observer while holding the lock, as it may replace itself
+    pub(crate) fn observe(&self, direction: Direction, method: &AMQPClass) {
+        let observer = self.0.read().clone();
+        if let Some(observer) = observer {
+            observer(direction, method)
+        }
+    }
+}
+
+impl fmt::Debug for FrameObserver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("FrameObserver").finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::connection::test_utils::*;
+    use crate::options::QueueDeclareOptions;
+    use crate::types::FieldTable;
+    use amq_protocol::protocol::{basic, AMQPClass};
+    use parking_lot::Mutex;
+
+    #[test]
+    fn observer_replaces_itself() {
+        let frame_observer = FrameObserver::default();
+        let observed = Arc::new(Mutex::new(Vec::new()));
+        let first_observed = observed.clone();
+        let first_frame_observer = frame_observer.clone();
+        frame_observer.set_observer(move |direction, _| {
+            first_observed.lock().push(("first", direction));
+            let second_observed = first_observed.clone();
+            first_frame_observer.set_observer(move |direction, _| {
+                second_observed.lock().push(("second", direction));
+            });
+        });
+
+        let method = AMQPClass::Basic(basic::AMQPMethod::RecoverOk(basic::RecoverOk {}));
+        frame_observer.observe(Direction::Outbound, &method);
+        frame_observer.observe(Direction::Inbound, &method);
+        assert_eq!(
+            *observed.lock(),
+            [
+                ("first", Direction::Outbound),
+                ("second", Direction::Inbound)
+            ]
+        );
+    }
+
+    #[test]
+    fn frame_observer() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        use crate::protocol::queue;
+        use parking_lot::Mutex;
+
+        let (conn, frames) = running_connection();
+        let observed = Arc::new(Mutex::new(Vec::new()));
+        let channel = run_against_server(&conn, &frames, conn.create_channel(), |frame| {
+            passive_declare_reply(frame, |_| None)
+        })
+        .unwrap();
+        let recorder = observed.clone();
+        conn.set_frame_observer(move |direction, method| {
+            recorder.lock().push((direction, method.clone()));
+        });
+        let queue = run_against_server(
+            &conn,
+            &frames,
+            channel.queue_declare(
+                "observed",
+                QueueDeclareOptions::default(),
+                FieldTable::default(),
+            ),
+            |frame| {
+                passive_declare_reply(frame, |method| match method {
+                    AMQPClass::Queue(queue::AMQPMethod::Declare(declare)) => Some(
+                        AMQPClass::Queue(queue::AMQPMethod::DeclareOk(queue::DeclareOk {
+                            queue: declare.queue.clone(),
+                            message_count: 0,
+                            consumer_count: 0,
+                        })),
+                    ),
+                    _ => None,
+                })
+            },
+        )
+        .unwrap();
+        assert_eq!(queue.name().as_str(), "observed");
+
+        let observed = observed.lock();
+        assert_eq!(observed.len(), 2);
+        assert!(matches!(
+            &observed[0],
+            (Direction::Outbound, AMQPClass::Queue(queue::AMQPMethod::Declare(declare)))
+                if declare.queue.as_str() == "observed"
+        ));
+        assert!(matches!(
+            &observed[1],
+            (Direction::Inbound, AMQPClass::Queue(queue::AMQPMethod::DeclareOk(declare_ok)))
+                if declare_ok.queue.as_str() == "observed"
+        ));
+    }
+}
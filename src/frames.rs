@@ -68,7 +68,10 @@ impl Frames {
                     .position(finder)
                     .and_then(|idx| replies.remove(idx))
             })
-            .map(|t| t.0)
+            .map(|t| {
+                trace!(channel=%channel_id, reply=?t.0, "got expected reply");
+                t.0
+            })
     }
 
     pub(crate) fn next_expected_close_ok_reply(
@@ -81,6 +84,20 @@ impl Frames {
             .next_expected_close_ok_reply(channel_id, error)
     }
 
+    pub(crate) fn expected_replies(&self, channel_id: ChannelId) -> Vec<String> {
+        self.inner
+            .lock()
+            .expected_replies
+            .get(&channel_id)
+            .map(|replies| {
+                replies
+                    .iter()
+                    .map(|reply| format!("{:?}", reply.0))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
     pub(crate) fn has_pending(&self) -> bool {
         self.inner.lock().has_pending()
     }
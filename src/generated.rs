@@ -236,7 +236,12 @@ pub(crate) enum Reply {
         ShortString,
         FieldTable,
     ),
-    QueueDeclareOk(PromiseResolver<Queue>, QueueDeclareOptions, FieldTable),
+    QueueDeclareOk(
+        PromiseResolver<Queue>,
+        ShortString,
+        QueueDeclareOptions,
+        FieldTable,
+    ),
     QueueBindOk(
         PromiseResolver<()>,
         ShortString,
@@ -378,6 +383,14 @@ impl Channel {
         }
     }
 
+    /// Limit the number of unacknowledged deliveries the server will send us.
+    ///
+    /// A `prefetch_count` of 0 means no limit. The prefetch size is always 0 (no limit) as
+    /// RabbitMQ doesn't support anything else.
+    ///
+    /// RabbitMQ reinterprets the `global` flag: when `false`, the limit applies separately to
+    /// each consumer started afterwards on this channel; when `true`, it is shared between
+    /// all the consumers of this channel.
     pub async fn basic_qos(
         &self,
         prefetch_count: ShortUInt,
@@ -621,7 +634,7 @@ impl Channel {
             ),
         }
     }
-    pub async fn basic_publish(
+    async fn do_basic_publish(
         &self,
         exchange: &str,
         routing_key: &str,
@@ -633,7 +646,8 @@ impl Channel {
             return Err(Error::InvalidChannelState(self.status.state()));
         }
 
-        let start_hook_res = self.before_basic_publish();
+        let start_hook_res =
+            self.before_basic_publish(exchange, routing_key, options, payload, &properties);
         let BasicPublishOptions {
             mandatory,
             immediate,
@@ -726,7 +740,7 @@ impl Channel {
         }
         self.on_basic_get_empty_received(method)
     }
-    pub async fn basic_ack(
+    async fn do_basic_ack(
         &self,
         delivery_tag: LongLongUInt,
         options: BasicAckOptions,
@@ -755,7 +769,7 @@ impl Channel {
         }
         self.on_basic_ack_received(method)
     }
-    pub async fn basic_reject(
+    async fn do_basic_reject(
         &self,
         delivery_tag: LongLongUInt,
         options: BasicRejectOptions,
@@ -777,6 +791,7 @@ impl Channel {
             promise.set_marker("basic.reject".into());
         }
         self.send_method_frame(method, send_resolver, None);
+        self.on_basic_reject_sent(delivery_tag);
         promise.await
     }
     pub async fn basic_recover_async(&self, options: BasicRecoverAsyncOptions) -> Result<()> {
@@ -849,7 +864,7 @@ impl Channel {
             ),
         }
     }
-    pub async fn basic_nack(
+    async fn do_basic_nack(
         &self,
         delivery_tag: LongLongUInt,
         options: BasicNackOptions,
@@ -1274,6 +1289,14 @@ impl Channel {
             ),
         }
     }
+    /// Ask the server to pause (`active: false`) or resume (`active: true`) the deliveries on this
+    /// channel, the outcome being available through [`ChannelStatus::receive_flow`].
+    ///
+    /// RabbitMQ doesn't support pausing deliveries this way: it answers `active: false` with a
+    /// `NOT_IMPLEMENTED` connection error, closing the whole connection and not only this channel.
+    /// Use [`Channel::basic_qos`] or [`Channel::basic_cancel`] to throttle or stop consumers there.
+    ///
+    /// [`ChannelStatus::receive_flow`]: ./struct.ChannelStatus.html#method.receive_flow
     pub async fn channel_flow(&self, options: ChannelFlowOptions) -> Result<Boolean> {
         if !self.status.connected() {
             return Err(Error::InvalidChannelState(self.status.state()));
@@ -1652,7 +1675,7 @@ impl Channel {
             ),
         }
     }
-    pub async fn exchange_bind(
+    async fn do_exchange_bind(
         &self,
         destination: &str,
         source: &str,
@@ -1738,7 +1761,7 @@ impl Channel {
             ),
         }
     }
-    pub async fn exchange_unbind(
+    async fn do_exchange_unbind(
         &self,
         destination: &str,
         source: &str,
@@ -1866,7 +1889,7 @@ impl Channel {
             method,
             send_resolver,
             Some(ExpectedReply(
-                Reply::QueueDeclareOk(resolver.clone(), options, creation_arguments),
+                Reply::QueueDeclareOk(resolver.clone(), queue.into(), options, creation_arguments),
                 Box::new(resolver),
             )),
         );
@@ -1887,9 +1910,8 @@ impl Channel {
         match self.frames.find_expected_reply(self.id, |reply| {
             matches!(&reply.0, Reply::QueueDeclareOk(..))
         }) {
-            Some(Reply::QueueDeclareOk(resolver, options, creation_arguments)) => {
-                self.on_queue_declare_ok_received(method, resolver, options, creation_arguments)
-            }
+            Some(Reply::QueueDeclareOk(resolver, queue, options, creation_arguments)) => self
+                .on_queue_declare_ok_received(method, resolver, queue, options, creation_arguments),
             unexpected => self.handle_invalid_contents(
                 format!(
                     "unexpected queue declare-ok received on channel {}, was awaiting for {:?}",
@@ -2016,6 +2038,11 @@ impl Channel {
                 Box::new(resolver),
             )),
         );
+        if nowait {
+            self.receive_queue_purge_ok(protocol::queue::PurgeOk {
+                ..Default::default()
+            })?;
+        }
         promise_out.await?;
         promise.await
     }
@@ -2367,6 +2394,9 @@ impl Channel {
                 Box::new(resolver),
             )),
         );
+        if nowait {
+            self.receive_confirm_select_ok(protocol::confirm::SelectOk {})?;
+        }
         promise_out.await?;
         promise.await
     }
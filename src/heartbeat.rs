@@ -41,6 +41,15 @@ impl Heartbeat {
         self.inner.lock().timeout = Some(timeout);
     }
 
+    pub(crate) fn set_read_timeout(&self, read_timeout: Duration) {
+        self.inner.lock().read_timeout = Some(read_timeout);
+    }
+
+    pub(crate) fn enabled(&self) -> bool {
+        let inner = self.inner.lock();
+        inner.timeout.is_some() || inner.read_timeout.is_some()
+    }
+
     pub(crate) fn killswitch(&self) -> KillSwitch {
         self.killswitch.clone()
     }
@@ -74,7 +83,9 @@ impl Heartbeat {
     }
 
     pub(crate) fn cancel(&self) {
-        self.inner.lock().timeout = None;
+        let mut inner = self.inner.lock();
+        inner.timeout = None;
+        inner.read_timeout = None;
     }
 }
 
@@ -88,6 +99,7 @@ struct Inner {
     last_read: Instant,
     last_write: Instant,
     timeout: Option<Duration>,
+    read_timeout: Option<Duration>,
 }
 
 impl Default for Inner {
@@ -96,12 +108,35 @@ impl Default for Inner {
             last_read: Instant::now(),
             last_write: Instant::now(),
             timeout: None,
+            read_timeout: None,
         }
     }
 }
 
 impl Inner {
     fn poll_timeout(&mut self, channels: &Channels, killswitch: &KillSwitch) -> Option<Duration> {
+        let Some(read_timeout) = self.read_timeout else {
+            return self.poll_heartbeat(channels, killswitch);
+        };
+
+        // Every frame counts, heartbeats included, so this catches peers which vanished without
+        // closing the TCP connection
+        let Some(read_delay) = read_timeout.checked_sub(self.last_read.elapsed()) else {
+            self.timeout = None;
+            self.read_timeout = None;
+            killswitch.kill();
+            channels.set_connection_error(Error::ReadTimeout(read_timeout));
+            return None;
+        };
+        let read_delay = read_delay.max(Duration::from_millis(1));
+        if self.timeout.is_none() {
+            return Some(read_delay);
+        }
+        self.poll_heartbeat(channels, killswitch)
+            .map(|timeout| timeout.min(read_delay))
+    }
+
+    fn poll_heartbeat(&mut self, channels: &Channels, killswitch: &KillSwitch) -> Option<Duration> {
         let timeout = self.timeout?;
 
         // The value stored in timeout is half the configured heartbeat value as the spec recommends to send heartbeats at twice the configured pace.
@@ -132,3 +167,134 @@ impl Inner {
         self.last_read = Instant::now();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        connection::test_utils::{connected_connection, connection_channels},
+        frames::Frames,
+        Connection, ConnectionState,
+    };
+    use amq_protocol::frame::AMQPFrame;
+
+    fn channels() -> (Connection, Channels, Frames) {
+        let (conn, _, frames) = connected_connection();
+        let channels = connection_channels(&conn);
+        (conn, channels, frames)
+    }
+
+    #[test]
+    fn heartbeat_sent_when_idle() {
+        let (_conn, channels, frames) = channels();
+        let killswitch = KillSwitch::default();
+        let timeout = Duration::from_secs(10);
+        let mut inner = Inner {
+            timeout: Some(timeout),
+            ..Default::default()
+        };
+
+        // We just wrote something, nothing to send yet
+        let next = inner.poll_timeout(&channels, &killswitch).unwrap();
+        assert!(next <= timeout && next > Duration::from_secs(9));
+        assert!(!frames.has_pending());
+
+        // Nothing written for longer than the timeout, send a heartbeat
+        inner.last_write = Instant::now() - Duration::from_secs(11);
+        assert_eq!(inner.poll_timeout(&channels, &killswitch), Some(timeout));
+        assert!(matches!(
+            frames.pop(true),
+            Some((AMQPFrame::Heartbeat(0), _))
+        ));
+        assert!(inner.last_write.elapsed() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn missing_heartbeat_is_a_connection_error() {
+        let (conn, channels, _) = channels();
+        let connection_status = conn.status().clone();
+        let killswitch = KillSwitch::default();
+        let mut inner = Inner {
+            timeout: Some(Duration::from_secs(10)),
+            ..Default::default()
+        };
+
+        inner.last_read = Instant::now() - Duration::from_secs(41);
+        assert_eq!(inner.poll_timeout(&channels, &killswitch), None);
+        assert!(killswitch.killed());
+        assert_eq!(connection_status.state(), ConnectionState::Error);
+    }
+
+    #[test]
+    fn read_timeout() {
+        let (conn, channels, _) = channels();
+        let connection_status = conn.status().clone();
+        let killswitch = KillSwitch::default();
+        let errors = Arc::new(Mutex::new(Vec::new()));
+        let on_error = errors.clone();
+        channels.set_error_handler(move |error| on_error.lock().push(error));
+        let mut inner = Inner {
+            timeout: Some(Duration::from_secs(10)),
+            read_timeout: Some(Duration::from_secs(5)),
+            ..Default::default()
+        };
+
+        // Wake up in time to check the read timeout, before the next heartbeat
+        let next = inner.poll_timeout(&channels, &killswitch).unwrap();
+        assert!(next <= Duration::from_secs(5) && next > Duration::from_secs(4));
+        inner.last_read = Instant::now() - Duration::from_secs(3);
+        let next = inner.poll_timeout(&channels, &killswitch).unwrap();
+        assert!(next <= Duration::from_secs(2) && next > Duration::from_secs(1));
+        assert!(!killswitch.killed());
+
+        // Nothing received at all, not even heartbeats
+        inner.last_read = Instant::now() - Duration::from_secs(6);
+        assert_eq!(inner.poll_timeout(&channels, &killswitch), None);
+        assert!(killswitch.killed());
+        assert_eq!(connection_status.state(), ConnectionState::Error);
+        assert_eq!(
+            *errors.lock(),
+            vec![Error::ReadTimeout(Duration::from_secs(5))]
+        );
+    }
+
+    #[test]
+    fn read_timeout_fires_when_nothing_is_received() {
+        let (conn, channels, _) = channels();
+        let connection_status = conn.status().clone();
+        let heartbeat = Heartbeat::new(
+            connection_status.clone(),
+            channels,
+            Arc::new(async_global_executor_trait::AsyncGlobalExecutor),
+            Arc::new(async_reactor_trait::AsyncIo),
+        );
+        heartbeat.set_read_timeout(Duration::from_millis(50));
+        heartbeat.start();
+
+        for _ in 0..100 {
+            if connection_status.errored() {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert!(connection_status.errored());
+        assert!(heartbeat.killswitch().killed());
+    }
+
+    #[test]
+    fn read_timeout_without_heartbeat() {
+        let (_conn, channels, _) = channels();
+        let killswitch = KillSwitch::default();
+        let mut inner = Inner {
+            read_timeout: Some(Duration::from_secs(5)),
+            ..Default::default()
+        };
+
+        inner.last_read = Instant::now() - Duration::from_secs(4);
+        let next = inner.poll_timeout(&channels, &killswitch).unwrap();
+        assert!(next <= Duration::from_secs(1));
+        inner.last_read = Instant::now() - Duration::from_secs(6);
+        assert_eq!(inner.poll_timeout(&channels, &killswitch), None);
+        assert!(killswitch.killed());
+    }
+}
@@ -23,6 +23,18 @@ impl<T: Default + Copy + AddAssign<T> + PartialEq<T> + PartialOrd<T> + From<u8>>
         self.id
     }
 
+    pub(crate) fn peek(&self) -> T {
+        if !self.allow_zero && self.id == self.zero {
+            self.one
+        } else if self.check_max() {
+            self.id
+        } else if self.allow_zero {
+            self.zero
+        } else {
+            self.one
+        }
+    }
+
     pub(crate) fn set_max(&mut self, max: T) {
         self.max = if max == self.zero { None } else { Some(max) };
     }
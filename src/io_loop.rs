@@ -111,6 +111,8 @@ impl IoLoop {
             if heartbeat != 0 {
                 let heartbeat = Duration::from_millis(u64::from(heartbeat) * 500); // * 1000 (ms) / 2 (half the negotiated timeout)
                 self.heartbeat.set_timeout(heartbeat);
+            }
+            if self.heartbeat.enabled() {
                 self.heartbeat.start();
             }
             self.status = Status::Connected;
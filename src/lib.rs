@@ -105,17 +105,24 @@ pub use amq_protocol::{
     types, uri,
 };
 
+pub use auto_ack::{AutoAck, Settlement};
+pub use capabilities::Capabilities;
 pub use channel::{options, Channel};
 pub use channel_status::{ChannelState, ChannelStatus};
 pub use configuration::Configuration;
 pub use connection::{Connect, Connection};
 pub use connection_properties::ConnectionProperties;
 pub use connection_status::{ConnectionState, ConnectionStatus};
-pub use consumer::{Consumer, ConsumerDelegate};
+pub use consumer::{Consumer, ConsumerArguments, ConsumerDelegate, MultiConsumer, StreamOffset};
 pub use consumer_status::ConsumerState;
 pub use error::{Error, Result};
-pub use exchange::ExchangeKind;
-pub use queue::Queue;
+pub use exchange::{ExchangeArguments, ExchangeDeclare, ExchangeKind, HeadersBinding};
+pub use frame_observer::Direction;
+pub use properties::{BasicPropertiesExt, HeadersMut};
+pub use qos::QosOptions;
+pub use queue::{Queue, QueueArguments, QueueBind, QueueDeclare, QueueType};
+pub use reconnecting_connection::{Backoff, ReconnectingConnection};
+pub use rpc_client::RpcClient;
 
 pub mod acker;
 pub mod heartbeat;
@@ -128,8 +135,10 @@ type Promise<T> = pinky_swear::PinkySwear<Result<T>>;
 type PromiseResolver<T> = pinky_swear::Pinky<Result<T>>;
 
 mod acknowledgement;
+mod auto_ack;
 mod basic_get_delivery;
 mod buffer;
+mod capabilities;
 mod channel;
 mod channel_closer;
 mod channel_receiver_state;
@@ -144,19 +153,25 @@ mod consumer;
 mod consumer_canceler;
 mod consumer_status;
 mod consumers;
+mod delivery_tags;
 mod error;
 mod error_handler;
 mod error_holder;
 mod exchange;
+mod frame_observer;
 mod frames;
 mod id_sequence;
 mod internal_rpc;
 mod io_loop;
 mod killswitch;
 mod parsing;
+mod properties;
+mod qos;
 mod queue;
+mod reconnecting_connection;
 mod registry;
 mod returned_messages;
+mod rpc_client;
 mod thread;
 mod topology_internal;
 mod wakers;
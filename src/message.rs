@@ -3,7 +3,7 @@ use crate::{
     error_holder::ErrorHolder,
     internal_rpc::InternalRPCHandle,
     protocol::AMQPError,
-    types::ShortString,
+    types::{AMQPValue, FieldTable, ShortString},
     types::{ChannelId, DeliveryTag, MessageCount, ReplyCode},
     BasicProperties, Result,
 };
@@ -13,7 +13,10 @@ use std::ops::{Deref, DerefMut};
 ///
 /// - Ok(Some(delivery)) carries the delivery alongside its channel
 /// - Ok(None) means that the consumer got canceled
-/// - Err(error) carries the error and is always followed by Ok(None)
+/// - Err(error) carries the error and is always followed by Ok(None), delegates get it through
+///   [`ConsumerDelegate::on_error`] instead
+///
+/// [`ConsumerDelegate::on_error`]: ../trait.ConsumerDelegate.html#method.on_error
 pub type DeliveryResult = Result<Option<Delivery>>;
 
 /// A received AMQP message.
@@ -39,6 +42,9 @@ pub struct Delivery {
     /// if no routing key is specified.
     pub routing_key: ShortString,
 
+    /// The queue the message was consumed or fetched from. Empty for returned messages.
+    pub queue: ShortString,
+
     /// Whether this message was redelivered
     pub redelivered: bool,
 
@@ -67,6 +73,7 @@ impl Delivery {
             delivery_tag,
             exchange,
             routing_key,
+            queue: ShortString::default(),
             redelivered,
             properties: BasicProperties::default(),
             data: Vec::default(),
@@ -74,8 +81,94 @@ impl Delivery {
         }
     }
 
+    /// Get a handle to ack, nack or reject this delivery, which can be moved to another task
+    /// or thread independently of the delivery itself.
+    ///
+    /// All the handles of a delivery share its state: it can only be settled once.
+    pub fn acker_clone(&self) -> Acker {
+        self.acker.clone()
+    }
+
     pub(crate) fn receive_content(&mut self, data: Vec<u8>) {
-        self.data.extend(data);
+        // Most messages fit in a single body frame, reuse its buffer instead of copying it
+        if self.data.is_empty() {
+            self.data = data;
+        } else {
+            self.data.extend(data);
+        }
+    }
+
+    /// Get the number of times this message has been dead-lettered, summed over all the
+    /// entries of the `x-death` header.
+    ///
+    /// Returns `None` if the message has never been dead-lettered.
+    pub fn death_count(&self) -> Option<u64> {
+        Some(
+            self.deaths()?
+                .filter_map(|death| death.inner().get("count").and_then(as_u64))
+                .sum(),
+        )
+    }
+
+    /// Get the reason why this message has been dead-lettered for the first time, e.g.
+    /// `rejected`, `expired` or `maxlen`.
+    pub fn first_death_reason(&self) -> Option<String> {
+        let headers = self.properties.headers().as_ref()?;
+        headers
+            .inner()
+            .get("x-first-death-reason")
+            .or_else(|| {
+                // x-death entries are sorted from the most recent to the oldest one
+                self.deaths()?.last()?.inner().get("reason")
+            })
+            .and_then(AMQPValue::as_long_string)
+            .map(ToString::to_string)
+    }
+
+    /// Deserialize the payload according to its `content_type` property.
+    ///
+    /// Only `application/json` is supported for now. Fails with [`Error::UnknownContentType`]
+    /// if the content type is missing or unsupported.
+    ///
+    /// [`Error::UnknownContentType`]: ../enum.Error.html#variant.UnknownContentType
+    #[cfg(feature = "json")]
+    pub fn decode<T: serde::de::DeserializeOwned>(&self) -> Result<T> {
+        let content_type = self.properties.content_type();
+        // Ignore parameters such as charset
+        match content_type
+            .as_ref()
+            .and_then(|content_type| content_type.as_str().split(';').next())
+            .map(str::trim)
+        {
+            Some("application/json") => serde_json::from_slice(&self.data)
+                .map_err(|error| crate::Error::DecodingError(std::sync::Arc::new(error))),
+            _ => Err(crate::Error::UnknownContentType(content_type.clone())),
+        }
+    }
+
+    fn deaths(&self) -> Option<impl DoubleEndedIterator<Item = &FieldTable>> {
+        let deaths = self
+            .properties
+            .headers()
+            .as_ref()?
+            .inner()
+            .get("x-death")?
+            .as_array()?;
+        Some(
+            deaths
+                .as_slice()
+                .iter()
+                .filter_map(AMQPValue::as_field_table),
+        )
+    }
+}
+
+fn as_u64(value: &AMQPValue) -> Option<u64> {
+    match value {
+        AMQPValue::LongLongInt(value) => u64::try_from(*value).ok(),
+        AMQPValue::LongInt(value) => u64::try_from(*value).ok(),
+        AMQPValue::LongUInt(value) => Some((*value).into()),
+        _ => None,
     }
 }
 
@@ -90,6 +183,9 @@ impl Deref for Delivery {
 #[derive(Debug, PartialEq)]
 pub struct BasicGetMessage {
     pub delivery: Delivery,
+
+    /// The number of messages left in the queue after this one, 0 meaning that this was the
+    /// last one.
     pub message_count: MessageCount,
 }
 
@@ -116,6 +212,11 @@ impl BasicGetMessage {
             message_count,
         }
     }
+
+    /// The number of messages left in the queue after this one.
+    pub fn remaining(&self) -> MessageCount {
+        self.message_count
+    }
 }
 
 impl Deref for BasicGetMessage {
@@ -132,6 +233,16 @@ impl DerefMut for BasicGetMessage {
     }
 }
 
+/// A message returned by the server because it couldn't be routed.
+///
+/// Returned messages were never delivered through a queue, thus they cannot be
+/// acknowledged: calling [`Acker::ack`], [`Acker::nack`] or [`Acker::reject`] on
+/// them results in an [`Error::UnboundAcker`].
+///
+/// [`Error::UnboundAcker`]: ../enum.Error.html#variant.UnboundAcker
+/// [`Acker::ack`]: ../struct.Acker.html#method.ack
+/// [`Acker::nack`]: ../struct.Acker.html#method.nack
+/// [`Acker::reject`]: ../struct.Acker.html#method.reject
 #[derive(Debug, PartialEq)]
 pub struct BasicReturnMessage {
     pub delivery: Delivery,
@@ -146,8 +257,10 @@ impl BasicReturnMessage {
         reply_code: ReplyCode,
         reply_text: ShortString,
     ) -> Self {
+        let mut delivery = Delivery::new(0, 0, exchange, routing_key, false, None, None);
+        delivery.acker = Acker::unbound();
         Self {
-            delivery: Delivery::new(0, 0, exchange, routing_key, false, None, None),
+            delivery,
             reply_code,
             reply_text,
         }
@@ -171,3 +284,351 @@ impl DerefMut for BasicReturnMessage {
         &mut self.delivery
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::connection::test_utils::*;
+    use crate::Error;
+
+    use crate::options::{BasicAckOptions, BasicNackOptions, BasicRejectOptions};
+    use crate::types::FieldTable;
+    use crate::BasicProperties;
+    use amq_protocol::frame::{AMQPContentHeader, AMQPFrame};
+    use amq_protocol::protocol::{basic, AMQPClass};
+    use futures_lite::future::block_on;
+
+    #[test]
+    fn returned_message_cannot_be_acked() {
+        let message = BasicReturnMessage::new(
+            "exchange".into(),
+            "routing-key".into(),
+            312,
+            "NO_ROUTE".into(),
+        );
+        assert_eq!(
+            block_on(message.ack(BasicAckOptions::default())),
+            Err(Error::UnboundAcker)
+        );
+        assert_eq!(
+            block_on(message.nack(BasicNackOptions::default())),
+            Err(Error::UnboundAcker)
+        );
+        assert_eq!(
+            block_on(message.reject(BasicRejectOptions::default())),
+            Err(Error::UnboundAcker)
+        );
+        assert!(!message.used());
+
+        // The deprecated default Acker is still a no-op
+        assert_eq!(
+            block_on(Acker::default().ack(BasicAckOptions::default())),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn ack_from_another_thread() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        use crate::options::BasicConsumeOptions;
+        use futures_lite::{future::poll_fn, StreamExt};
+        use std::task::Poll;
+
+        fn assert_send_sync<T: Send + Sync + 'static>(_: &T) {}
+
+        let (conn, frames) = running_connection();
+        let mut acked = Vec::new();
+        let mut server = |frame: &AMQPFrame| match frame {
+            AMQPFrame::Method(_, AMQPClass::Basic(basic::AMQPMethod::Ack(ack))) => {
+                acked.push(ack.delivery_tag);
+                None
+            }
+            frame => passive_declare_reply(frame, |method| match method {
+                AMQPClass::Basic(basic::AMQPMethod::Consume(consume)) => Some(AMQPClass::Basic(
+                    basic::AMQPMethod::ConsumeOk(basic::ConsumeOk {
+                        consumer_tag: consume.consumer_tag.clone(),
+                    }),
+                )),
+                _ => None,
+            }),
+        };
+        let channel =
+            run_against_server(&conn, &frames, conn.create_channel(), &mut server).unwrap();
+        let mut consumer = run_against_server(
+            &conn,
+            &frames,
+            channel.basic_consume(
+                "queue",
+                "consumer",
+                BasicConsumeOptions::default(),
+                FieldTable::default(),
+            ),
+            &mut server,
+        )
+        .unwrap();
+        deliver(&conn, &channel, "consumer", 2, b"");
+        let delivery = block_on(consumer.next()).unwrap().unwrap();
+        let acker = delivery.acker_clone();
+        assert_send_sync(&acker);
+        let other = delivery.acker_clone();
+        drop(delivery);
+
+        let ack = std::thread::spawn(move || block_on(acker.ack(BasicAckOptions::default())));
+        run_against_server(
+            &conn,
+            &frames,
+            poll_fn(|_| {
+                if ack.is_finished() {
+                    Poll::Ready(Ok(()))
+                } else {
+                    Poll::Pending
+                }
+            }),
+            &mut server,
+        )
+        .unwrap();
+        ack.join().unwrap().unwrap();
+        assert_eq!(acked, vec![2]);
+        assert!(other.used());
+        assert!(block_on(other.ack(BasicAckOptions::default())).is_err());
+    }
+
+    #[test]
+    fn single_body_frame_is_not_copied() {
+        let frame = vec![42; 64];
+        let buffer = frame.as_ptr();
+        let mut delivery = Delivery::new(1, 1, "".into(), "".into(), false, None, None);
+        delivery.receive_content(frame);
+        assert_eq!(delivery.data.as_ptr(), buffer);
+        delivery.receive_content(vec![43; 64]);
+        assert_eq!(delivery.data.len(), 128);
+        assert_eq!(delivery.data[64..], [43; 64]);
+    }
+
+    #[test]
+    fn wrappers_deref_to_delivery() {
+        let mut get_message = BasicGetMessage {
+            delivery: Delivery::new(1, 2, "".into(), "get".into(), false, None, None),
+            message_count: 3,
+        };
+        get_message.receive_content(b"got".to_vec());
+        assert_eq!(get_message.data, b"got");
+        assert_eq!(get_message.routing_key.as_str(), "get");
+        assert_eq!(get_message.delivery_tag, 2);
+
+        let mut return_message =
+            BasicReturnMessage::new("exchange".into(), "returned".into(), 312, "NO_ROUTE".into());
+        return_message.receive_content(b"returned".to_vec());
+        assert_eq!(return_message.data, b"returned");
+        assert_eq!(return_message.routing_key.as_str(), "returned");
+        assert_eq!(return_message.delivery_tag, 0);
+    }
+
+    fn death(queue: &str, reason: &str, count: i64) -> AMQPValue {
+        let mut death = FieldTable::default();
+        death.insert("queue".into(), AMQPValue::LongString(queue.into()));
+        death.insert("reason".into(), AMQPValue::LongString(reason.into()));
+        death.insert("count".into(), AMQPValue::LongLongInt(count));
+        AMQPValue::FieldTable(death)
+    }
+
+    fn delivery_with_deaths(deaths: Vec<AMQPValue>) -> Delivery {
+        let mut headers = FieldTable::default();
+        headers.insert("x-death".into(), AMQPValue::FieldArray(deaths.into()));
+        let mut delivery = Delivery::new(1, 1, "".into(), "".into(), true, None, None);
+        delivery.properties = BasicProperties::default().with_headers(headers);
+        delivery
+    }
+
+    #[test]
+    fn delivery_without_death() {
+        let delivery = Delivery::new(1, 1, "".into(), "".into(), false, None, None);
+        assert_eq!(delivery.death_count(), None);
+        assert_eq!(delivery.first_death_reason(), None);
+    }
+
+    #[test]
+    fn delivery_single_death() {
+        let delivery = delivery_with_deaths(vec![death("work", "rejected", 2)]);
+        assert_eq!(delivery.death_count(), Some(2));
+        assert_eq!(delivery.first_death_reason().as_deref(), Some("rejected"));
+    }
+
+    #[test]
+    fn delivery_multiple_deaths() {
+        let delivery = delivery_with_deaths(vec![
+            death("retry", "expired", 3),
+            death("work", "rejected", 3),
+        ]);
+        assert_eq!(delivery.death_count(), Some(6));
+        assert_eq!(delivery.first_death_reason().as_deref(), Some("rejected"));
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn decode_json_payload() {
+        #[derive(Debug, PartialEq, serde::Deserialize)]
+        struct Order {
+            id: u32,
+            items: Vec<String>,
+        }
+
+        let mut delivery = Delivery::new(1, 1, "".into(), "".into(), false, None, None);
+        delivery.receive_content(br#"{"id": 42, "items": ["book"]}"#.to_vec());
+        delivery.properties =
+            BasicProperties::default().with_content_type("application/json; charset=utf-8".into());
+        assert_eq!(
+            delivery.decode::<Order>(),
+            Ok(Order {
+                id: 42,
+                items: vec!["book".into()],
+            })
+        );
+        assert!(matches!(
+            delivery.decode::<String>(),
+            Err(crate::Error::DecodingError(_))
+        ));
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn decode_unknown_content_type() {
+        let mut delivery = Delivery::new(1, 1, "".into(), "".into(), false, None, None);
+        delivery.receive_content(b"{}".to_vec());
+        assert_eq!(
+            delivery.decode::<serde_json::Value>(),
+            Err(crate::Error::UnknownContentType(None))
+        );
+        delivery.properties =
+            BasicProperties::default().with_content_type("application/msgpack".into());
+        assert_eq!(
+            delivery.decode::<serde_json::Value>(),
+            Err(crate::Error::UnknownContentType(Some(
+                "application/msgpack".into()
+            )))
+        );
+    }
+
+    #[test]
+    fn dead_letter_and_requeue() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        use crate::options::BasicGetOptions;
+
+        let (conn, frames) = running_connection();
+        let mut delivery_tag = 0;
+        let mut rejected = Vec::new();
+        let mut server = |frame: &AMQPFrame| match frame {
+            AMQPFrame::Method(id, AMQPClass::Basic(basic::AMQPMethod::Get(_))) => {
+                delivery_tag += 1;
+                vec![
+                    AMQPFrame::Method(
+                        *id,
+                        AMQPClass::Basic(basic::AMQPMethod::GetOk(basic::GetOk {
+                            delivery_tag,
+                            redelivered: false,
+                            exchange: "".into(),
+                            routing_key: "queue".into(),
+                            message_count: 0,
+                        })),
+                    ),
+                    AMQPFrame::Header(
+                        *id,
+                        60,
+                        Box::new(AMQPContentHeader {
+                            class_id: 60,
+                            body_size: 0,
+                            properties: BasicProperties::default(),
+                        }),
+                    ),
+                ]
+            }
+            AMQPFrame::Method(_, AMQPClass::Basic(basic::AMQPMethod::Reject(reject))) => {
+                rejected.push((reject.delivery_tag, reject.requeue));
+                Vec::new()
+            }
+            frame => passive_declare_reply(frame, |_| None).into_iter().collect(),
+        };
+
+        let channel =
+            run_against_server(&conn, &frames, conn.create_channel(), &mut server).unwrap();
+        let mut messages = Vec::new();
+        for _ in 0..4 {
+            messages.push(
+                run_against_server(
+                    &conn,
+                    &frames,
+                    channel.basic_get("queue", BasicGetOptions::default()),
+                    &mut server,
+                )
+                .unwrap()
+                .unwrap(),
+            );
+        }
+        run_against_server(&conn, &frames, messages[0].dead_letter(), &mut server).unwrap();
+        run_against_server(&conn, &frames, messages[1].requeue(), &mut server).unwrap();
+        run_against_server(&conn, &frames, channel.basic_dead_letter(3), &mut server).unwrap();
+        run_against_server(&conn, &frames, channel.basic_requeue(4), &mut server).unwrap();
+        assert_eq!(rejected, vec![(1, false), (2, true), (3, false), (4, true)]);
+    }
+
+    #[test]
+    fn basic_get_message_can_be_acked() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        use crate::acker::Acker;
+        use crate::options::{BasicAckOptions, BasicGetOptions};
+
+        let (conn, frames) = running_connection();
+        let mut acked = None;
+        let mut server = |frame: &AMQPFrame| match frame {
+            AMQPFrame::Method(id, AMQPClass::Basic(basic::AMQPMethod::Get(_))) => vec![
+                AMQPFrame::Method(
+                    *id,
+                    AMQPClass::Basic(basic::AMQPMethod::GetOk(basic::GetOk {
+                        delivery_tag: 7,
+                        redelivered: false,
+                        exchange: "".into(),
+                        routing_key: "queue".into(),
+                        message_count: 0,
+                    })),
+                ),
+                AMQPFrame::Header(
+                    *id,
+                    60,
+                    Box::new(AMQPContentHeader {
+                        class_id: 60,
+                        body_size: 0,
+                        properties: BasicProperties::default(),
+                    }),
+                ),
+            ],
+            AMQPFrame::Method(id, AMQPClass::Basic(basic::AMQPMethod::Ack(ack))) => {
+                acked = Some((*id, ack.delivery_tag));
+                Vec::new()
+            }
+            frame => passive_declare_reply(frame, |_| None).into_iter().collect(),
+        };
+
+        let channel =
+            run_against_server(&conn, &frames, conn.create_channel(), &mut server).unwrap();
+        let message = run_against_server(
+            &conn,
+            &frames,
+            channel.basic_get("queue", BasicGetOptions::default()),
+            &mut server,
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(message.acker, Acker::new(channel.id(), 7, None, None));
+        run_against_server(
+            &conn,
+            &frames,
+            message.ack(BasicAckOptions::default()),
+            &mut server,
+        )
+        .unwrap();
+        assert_eq!(acked, Some((channel.id(), 7)));
+    }
+}
@@ -0,0 +1,221 @@
+use crate::{
+    types::{AMQPValue, FieldArray, FieldTable, LongString, ShortShortUInt},
+    BasicProperties,
+};
+use std::{
+    mem,
+    ops::{Deref, DerefMut},
+};
+
+pub(crate) const TRANSIENT_DELIVERY_MODE: ShortShortUInt = 1;
+pub(crate) const PERSISTENT_DELIVERY_MODE: ShortShortUInt = 2;
+
+/// Helpers to build the [`BasicProperties`] of a message.
+///
+/// `BasicProperties` is defined in `amq-protocol`, import this trait to use them.
+///
+/// ```rust
+/// use lapin::{types::LongString, BasicProperties, BasicPropertiesExt};
+///
+/// let properties = BasicProperties::default()
+///     .with_header("x-trace-id", LongString::from("4bf92f3577b34da6"))
+///     .with_header("x-attempt", 3i64)
+///     .with_header("x-replay", false);
+/// ```
+pub trait BasicPropertiesExt {
+    /// Properties of a message which survives a server restart when it gets routed to durable
+    /// queues.
+    fn persistent() -> Self;
+
+    /// Properties of a message which is only kept in memory, and is lost if the server restarts.
+    fn transient() -> Self;
+
+    /// Add a header, creating the headers table if there isn't one yet.
+    ///
+    /// Any primitive AMQP type can be passed directly, strings have to be wrapped in a
+    /// [`LongString`](./types/struct.LongString.html) or a
+    /// [`ShortString`](./types/struct.ShortString.html).
+    #[must_use]
+    fn with_header<V: Into<AMQPValue>>(self, key: &str, value: V) -> Self;
+
+    /// Edit the headers in place, creating the headers table if there isn't one yet.
+    ///
+    /// The changes are written back to the properties when the returned guard is dropped.
+    fn headers_mut(&mut self) -> HeadersMut<'_>;
+
+    /// Also route the message with these routing keys (`CC` header, sender-selected
+    /// distribution), the header is delivered to the consumers.
+    #[must_use]
+    fn with_cc(self, routing_keys: &[&str]) -> Self;
+
+    /// Also route the message with these routing keys (`BCC` header, sender-selected
+    /// distribution), the broker strips the header before delivering the message.
+    #[must_use]
+    fn with_bcc(self, routing_keys: &[&str]) -> Self;
+}
+
+impl BasicPropertiesExt for BasicProperties {
+    fn persistent() -> Self {
+        Self::default().with_delivery_mode(PERSISTENT_DELIVERY_MODE)
+    }
+
+    fn transient() -> Self {
+        Self::default().with_delivery_mode(TRANSIENT_DELIVERY_MODE)
+    }
+
+    fn with_header<V: Into<AMQPValue>>(self, key: &str, value: V) -> Self {
+        let mut headers = self.headers().clone().unwrap_or_default();
+        headers.insert(key.into(), value.into());
+        self.with_headers(headers)
+    }
+
+    fn headers_mut(&mut self) -> HeadersMut<'_> {
+        let headers = self.headers().clone().unwrap_or_default();
+        HeadersMut {
+            properties: self,
+            headers,
+        }
+    }
+
+    fn with_cc(self, routing_keys: &[&str]) -> Self {
+        self.with_header("CC", routing_keys_array(routing_keys))
+    }
+
+    fn with_bcc(self, routing_keys: &[&str]) -> Self {
+        self.with_header("BCC", routing_keys_array(routing_keys))
+    }
+}
+
+/// The headers of a [`BasicProperties`] being edited, see [`BasicPropertiesExt::headers_mut`].
+pub struct HeadersMut<'a> {
+    properties: &'a mut BasicProperties,
+    headers: FieldTable,
+}
+
+impl Deref for HeadersMut<'_> {
+    type Target = FieldTable;
+
+    fn deref(&self) -> &Self::Target {
+        &self.headers
+    }
+}
+
+impl DerefMut for HeadersMut<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.headers
+    }
+}
+
+impl Drop for HeadersMut<'_> {
+    fn drop(&mut self) {
+        *self.properties = mem::take(self.properties).with_headers(mem::take(&mut self.headers));
+    }
+}
+
+fn routing_keys_array(routing_keys: &[&str]) -> AMQPValue {
+    AMQPValue::FieldArray(FieldArray::from(
+        routing_keys
+            .iter()
+            .map(|routing_key| AMQPValue::LongString(LongString::from(*routing_key)))
+            .collect::<Vec<_>>(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::ShortString;
+
+    #[test]
+    fn delivery_modes() {
+        assert_eq!(BasicProperties::persistent().delivery_mode(), &Some(2));
+        assert_eq!(BasicProperties::transient().delivery_mode(), &Some(1));
+        assert_eq!(
+            BasicProperties::persistent().with_header("attempt", 1i64),
+            BasicProperties::default()
+                .with_delivery_mode(2)
+                .with_header("attempt", 1i64)
+        );
+    }
+
+    #[test]
+    fn with_header() {
+        let properties = BasicProperties::default()
+            .with_content_type("text/plain".into())
+            .with_header("trace", LongString::from("abc"))
+            .with_header("origin", ShortString::from("billing"))
+            .with_header("attempt", 3i64)
+            .with_header("replay", true)
+            .with_header("ratio", 0.5f64)
+            .with_header("attempt", 4i64);
+
+        assert_eq!(properties.content_type(), &Some("text/plain".into()));
+        let headers = properties.headers().as_ref().unwrap().inner();
+        assert_eq!(headers.len(), 5);
+        assert_eq!(
+            headers.get("trace"),
+            Some(&AMQPValue::LongString("abc".into()))
+        );
+        assert_eq!(
+            headers.get("origin"),
+            Some(&AMQPValue::ShortString("billing".into()))
+        );
+        assert_eq!(headers.get("attempt"), Some(&AMQPValue::LongLongInt(4)));
+        assert_eq!(headers.get("replay"), Some(&AMQPValue::Boolean(true)));
+        assert_eq!(headers.get("ratio"), Some(&AMQPValue::Double(0.5)));
+    }
+
+    #[test]
+    fn headers_mut() {
+        let mut properties = BasicProperties::default()
+            .with_content_type("text/plain".into())
+            .with_header("attempt", 3i64);
+
+        {
+            let mut headers = properties.headers_mut();
+            headers.insert("attempt".into(), AMQPValue::LongLongInt(4));
+            headers.insert("trace".into(), AMQPValue::LongString("abc".into()));
+        }
+
+        assert_eq!(properties.content_type(), &Some("text/plain".into()));
+        let headers = properties.headers().as_ref().unwrap().inner();
+        assert_eq!(headers.len(), 2);
+        assert_eq!(headers.get("attempt"), Some(&AMQPValue::LongLongInt(4)));
+        assert_eq!(
+            headers.get("trace"),
+            Some(&AMQPValue::LongString("abc".into()))
+        );
+
+        let mut properties = BasicProperties::default();
+        properties
+            .headers_mut()
+            .insert("replay".into(), AMQPValue::Boolean(true));
+        assert_eq!(
+            properties.headers().as_ref().unwrap().inner().get("replay"),
+            Some(&AMQPValue::Boolean(true))
+        );
+    }
+
+    #[test]
+    fn with_cc_and_bcc() {
+        let properties = BasicProperties::default()
+            .with_cc(&["orders.eu", "orders.us"])
+            .with_bcc(&["audit"]);
+
+        let headers = properties.headers().as_ref().unwrap().inner();
+        assert_eq!(headers.len(), 2);
+        assert_eq!(
+            headers.get("CC"),
+            Some(&AMQPValue::FieldArray(FieldArray::from(vec![
+                AMQPValue::LongString("orders.eu".into()),
+                AMQPValue::LongString("orders.us".into()),
+            ])))
+        );
+        assert_eq!(
+            headers.get("BCC"),
+            Some(&AMQPValue::FieldArray(FieldArray::from(vec![
+                AMQPValue::LongString("audit".into()),
+            ])))
+        );
+    }
+}
@@ -1,4 +1,10 @@
-use crate::{message::BasicReturnMessage, returned_messages::ReturnedMessages, Promise, Result};
+use crate::{
+    message::BasicReturnMessage,
+    options::BasicPublishOptions,
+    returned_messages::ReturnedMessages,
+    types::{DeliveryTag, ShortString},
+    BasicProperties, Promise, Result,
+};
 use std::{
     fmt,
     future::Future,
@@ -7,7 +13,9 @@ use std::{
 };
 use tracing::trace;
 
+/// The confirmation of a published message, resolving once the server has acked or nacked it.
 pub struct PublisherConfirm {
+    delivery_tag: Option<DeliveryTag>,
     inner: Option<Promise<Confirmation>>,
     returned_messages: ReturnedMessages,
 }
@@ -37,9 +45,37 @@ impl Confirmation {
     }
 }
 
+/// Counters of the publishes made on a channel and of what the server answered.
+///
+/// They only ever increase, until the channel gets closed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ConfirmStats {
+    pub published: u64,
+    pub confirmed: u64,
+    pub nacked: u64,
+    pub returned: u64,
+}
+
+/// A message published on a channel with automatic resend enabled.
+///
+/// See [`Channel::enable_auto_resend`](../struct.Channel.html#method.enable_auto_resend).
+#[derive(Clone, Debug, PartialEq)]
+pub struct PublishRequest {
+    pub exchange: ShortString,
+    pub routing_key: ShortString,
+    pub options: BasicPublishOptions,
+    pub payload: Vec<u8>,
+    pub properties: BasicProperties,
+}
+
 impl PublisherConfirm {
-    pub(crate) fn new(inner: Promise<Confirmation>, returned_messages: ReturnedMessages) -> Self {
+    pub(crate) fn new(
+        delivery_tag: DeliveryTag,
+        inner: Promise<Confirmation>,
+        returned_messages: ReturnedMessages,
+    ) -> Self {
         Self {
+            delivery_tag: Some(delivery_tag),
             inner: Some(inner),
             returned_messages,
         }
@@ -47,15 +83,29 @@ impl PublisherConfirm {
 
     pub(crate) fn not_requested(returned_messages: ReturnedMessages) -> Self {
         Self {
+            delivery_tag: None,
             inner: Some(Promise::new_with_data(Ok(Confirmation::NotRequested))),
             returned_messages,
         }
     }
+
+    /// The delivery tag the message was published with, `None` if the channel isn't in
+    /// confirm mode.
+    ///
+    /// With [`Channel::enable_auto_resend`], a resent message gets a new delivery tag but this
+    /// handle still resolves with its final confirmation.
+    ///
+    /// [`Channel::enable_auto_resend`]: ../struct.Channel.html#method.enable_auto_resend
+    pub fn delivery_tag(&self) -> Option<DeliveryTag> {
+        self.delivery_tag
+    }
 }
 
 impl fmt::Debug for PublisherConfirm {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.debug_struct("PublisherConfirm").finish()
+        f.debug_struct("PublisherConfirm")
+            .field("delivery_tag", &self.delivery_tag)
+            .finish()
     }
 }
 
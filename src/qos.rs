@@ -0,0 +1,27 @@
+use crate::{options::BasicQosOptions, types::ShortUInt};
+
+/// Named arguments for `basic_qos`, see [`Connection::set_qos`].
+///
+/// RabbitMQ reinterprets the `global` flag: when `false` (the default), `prefetch_count`
+/// applies separately to each consumer started afterwards on the channel; when `true`, it is
+/// shared between all the consumers of the channel.
+///
+/// There is no `prefetch_size`: it is always sent as 0 (no limit), as RabbitMQ closes the
+/// connection with NOT_IMPLEMENTED for anything else.
+///
+/// [`Connection::set_qos`]: ./struct.Connection.html#method.set_qos
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct QosOptions {
+    /// The number of unacknowledged deliveries the server will send us, 0 meaning no limit.
+    pub prefetch_count: ShortUInt,
+    /// Share the limit between all the consumers of the channel.
+    pub global: bool,
+}
+
+impl QosOptions {
+    pub(crate) fn basic_qos_options(&self) -> BasicQosOptions {
+        BasicQosOptions {
+            global: self.global,
+        }
+    }
+}
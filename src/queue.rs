@@ -1,4 +1,10 @@
-use crate::types::{ConsumerCount, MessageCount, ShortString};
+use crate::{
+    options::{QueueBindOptions, QueueDeclareOptions},
+    types::{
+        AMQPValue, ConsumerCount, FieldTable, LongString, LongUInt, MessageCount, ShortString,
+    },
+    Error, Result,
+};
 use std::borrow::Borrow;
 
 #[derive(Clone, Debug)]
@@ -39,3 +45,346 @@ impl Borrow<str> for Queue {
         self.name.as_str()
     }
 }
+
+/// Typed builder for the most common `queue_declare` arguments.
+///
+/// ```rust
+/// use lapin::{types::FieldTable, QueueArguments};
+///
+/// let arguments: FieldTable = QueueArguments::default()
+///     .dead_letter_exchange("dlx")
+///     .message_ttl(60_000)
+///     .into();
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct QueueArguments(FieldTable);
+
+impl QueueArguments {
+    /// Republish dead-lettered messages to this exchange (`x-dead-letter-exchange`).
+    #[must_use]
+    pub fn dead_letter_exchange(self, exchange: &str) -> Self {
+        self.string("x-dead-letter-exchange", exchange)
+    }
+
+    /// Replace the routing key of dead-lettered messages (`x-dead-letter-routing-key`).
+    #[must_use]
+    pub fn dead_letter_routing_key(self, routing_key: &str) -> Self {
+        self.string("x-dead-letter-routing-key", routing_key)
+    }
+
+    /// Discard messages older than `ttl` milliseconds (`x-message-ttl`).
+    #[must_use]
+    pub fn message_ttl(self, ttl: LongUInt) -> Self {
+        self.argument("x-message-ttl", AMQPValue::LongUInt(ttl))
+    }
+
+    /// Limit the number of ready messages in the queue (`x-max-length`).
+    #[must_use]
+    pub fn max_length(self, max_length: LongUInt) -> Self {
+        self.argument("x-max-length", AMQPValue::LongUInt(max_length))
+    }
+
+    /// Only deliver messages to one consumer at a time, the other ones taking over when it goes
+    /// away (`x-single-active-consumer`).
+    #[must_use]
+    pub fn single_active_consumer(self) -> Self {
+        self.argument("x-single-active-consumer", AMQPValue::Boolean(true))
+    }
+
+    /// Get the arguments to pass to `queue_declare`.
+    pub fn into_field_table(self) -> FieldTable {
+        self.0
+    }
+
+    fn string(self, key: &str, value: &str) -> Self {
+        self.argument(key, AMQPValue::LongString(LongString::from(value)))
+    }
+
+    fn argument(mut self, key: &str, value: AMQPValue) -> Self {
+        self.0.insert(key.into(), value);
+        self
+    }
+}
+
+impl From<QueueArguments> for FieldTable {
+    fn from(arguments: QueueArguments) -> Self {
+        arguments.into_field_table()
+    }
+}
+
+/// The type of a RabbitMQ queue (`x-queue-type`).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum QueueType {
+    #[default]
+    Classic,
+    /// A replicated queue, which has to be durable and can't be exclusive nor auto-delete.
+    Quorum,
+    /// An append-only log, with the same constraints as quorum queues.
+    Stream,
+}
+
+impl QueueType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            QueueType::Classic => "classic",
+            QueueType::Quorum => "quorum",
+            QueueType::Stream => "stream",
+        }
+    }
+}
+
+/// Builder for the parameters of `queue_declare`.
+///
+/// Every flag defaults to `false` and there are no arguments unless specified.
+///
+/// ```rust
+/// use lapin::{QueueArguments, QueueDeclare};
+///
+/// let declare = QueueDeclare::new("jobs")
+///     .durable()
+///     .arguments(QueueArguments::default().dead_letter_exchange("dlx"));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct QueueDeclare {
+    pub(crate) queue: ShortString,
+    pub(crate) options: QueueDeclareOptions,
+    pub(crate) arguments: FieldTable,
+    pub(crate) queue_type: Option<QueueType>,
+}
+
+impl QueueDeclare {
+    pub fn new(queue: &str) -> Self {
+        Self {
+            queue: queue.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Let the server generate the name of the queue, it's then available through
+    /// [`Queue::name`].
+    pub fn anonymous() -> Self {
+        Self::default()
+    }
+
+    /// Only check whether the queue exists, without creating it.
+    #[must_use]
+    pub fn passive(mut self) -> Self {
+        self.options.passive = true;
+        self
+    }
+
+    /// Make the queue survive a broker restart.
+    #[must_use]
+    pub fn durable(mut self) -> Self {
+        self.options.durable = true;
+        self
+    }
+
+    /// Only allow our connection to use the queue, and delete it once it's closed.
+    #[must_use]
+    pub fn exclusive(mut self) -> Self {
+        self.options.exclusive = true;
+        self
+    }
+
+    /// Delete the queue once it no longer has any consumer.
+    #[must_use]
+    pub fn auto_delete(mut self) -> Self {
+        self.options.auto_delete = true;
+        self
+    }
+
+    /// Don't wait for the server to confirm the declaration.
+    #[must_use]
+    pub fn nowait(mut self) -> Self {
+        self.options.nowait = true;
+        self
+    }
+
+    #[must_use]
+    pub fn arguments<A: Into<FieldTable>>(mut self, arguments: A) -> Self {
+        self.arguments = arguments.into();
+        self
+    }
+
+    /// Set the type of the queue (`x-queue-type`), taking precedence over the arguments.
+    ///
+    /// Quorum queues and streams have to be durable and can be neither exclusive nor
+    /// auto-delete, which is checked before anything is sent to the server.
+    #[must_use]
+    pub fn queue_type(mut self, queue_type: QueueType) -> Self {
+        self.queue_type = Some(queue_type);
+        self
+    }
+
+    /// Check the declaration and inject `x-queue-type` in the arguments.
+    pub(crate) fn prepare(mut self) -> Result<Self> {
+        if let Some(queue_type) = self.queue_type {
+            if queue_type != QueueType::Classic && !self.options.passive {
+                let invalid = if !self.options.durable {
+                    Some("non durable")
+                } else if self.options.exclusive {
+                    Some("exclusive")
+                } else if self.options.auto_delete {
+                    Some("auto-delete")
+                } else {
+                    None
+                };
+                if let Some(invalid) = invalid {
+                    return Err(Error::InvalidQueueArguments(format!(
+                        "{} queues cannot be {}",
+                        queue_type.as_str(),
+                        invalid
+                    )));
+                }
+            }
+            self.arguments.insert(
+                "x-queue-type".into(),
+                AMQPValue::LongString(queue_type.as_str().into()),
+            );
+        }
+        Ok(self)
+    }
+}
+
+/// Builder for the parameters of `queue_bind`, to bind several queues at once with
+/// [`Channel::queue_bind_many`].
+///
+/// ```rust
+/// use lapin::QueueBind;
+///
+/// let binds = ["eu", "us"]
+///     .map(|region| QueueBind::new("orders", "amq.topic", &format!("orders.{}", region)));
+/// ```
+///
+/// [`Channel::queue_bind_many`]: ./struct.Channel.html#method.queue_bind_many
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct QueueBind {
+    pub(crate) queue: ShortString,
+    pub(crate) exchange: ShortString,
+    pub(crate) routing_key: ShortString,
+    pub(crate) options: QueueBindOptions,
+    pub(crate) arguments: FieldTable,
+}
+
+impl QueueBind {
+    pub fn new(queue: &str, exchange: &str, routing_key: &str) -> Self {
+        Self {
+            queue: queue.into(),
+            exchange: exchange.into(),
+            routing_key: routing_key.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Don't wait for the server to confirm the binding.
+    #[must_use]
+    pub fn nowait(mut self) -> Self {
+        self.options.nowait = true;
+        self
+    }
+
+    #[must_use]
+    pub fn arguments<A: Into<FieldTable>>(mut self, arguments: A) -> Self {
+        self.arguments = arguments.into();
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn queue_arguments() {
+        let arguments = QueueArguments::default()
+            .dead_letter_exchange("dlx")
+            .dead_letter_routing_key("dead")
+            .message_ttl(60_000)
+            .max_length(1_000)
+            .single_active_consumer()
+            .into_field_table();
+        let arguments = arguments.inner();
+        assert_eq!(arguments.len(), 5);
+        assert_eq!(
+            arguments.get("x-dead-letter-exchange"),
+            Some(&AMQPValue::LongString("dlx".into()))
+        );
+        assert_eq!(
+            arguments.get("x-dead-letter-routing-key"),
+            Some(&AMQPValue::LongString("dead".into()))
+        );
+        assert_eq!(
+            arguments.get("x-message-ttl"),
+            Some(&AMQPValue::LongUInt(60_000))
+        );
+        assert_eq!(
+            arguments.get("x-max-length"),
+            Some(&AMQPValue::LongUInt(1_000))
+        );
+        assert_eq!(
+            arguments.get("x-single-active-consumer"),
+            Some(&AMQPValue::Boolean(true))
+        );
+    }
+
+    #[test]
+    fn queue_type() {
+        for queue_type in [QueueType::Classic, QueueType::Quorum, QueueType::Stream] {
+            let declare = QueueDeclare::new("jobs")
+                .durable()
+                .queue_type(queue_type)
+                .prepare()
+                .unwrap();
+            assert_eq!(
+                declare.arguments.inner().get("x-queue-type"),
+                Some(&AMQPValue::LongString(queue_type.as_str().into()))
+            );
+        }
+        assert!(QueueDeclare::anonymous()
+            .exclusive()
+            .queue_type(QueueType::Classic)
+            .prepare()
+            .is_ok());
+        assert!(QueueDeclare::new("jobs")
+            .passive()
+            .queue_type(QueueType::Quorum)
+            .prepare()
+            .is_ok());
+        assert!(QueueDeclare::new("jobs")
+            .prepare()
+            .unwrap()
+            .arguments
+            .inner()
+            .is_empty());
+    }
+
+    #[test]
+    fn invalid_queue_type() {
+        for (declare, message) in [
+            (
+                QueueDeclare::new("jobs").queue_type(QueueType::Quorum),
+                "quorum queues cannot be non durable",
+            ),
+            (
+                QueueDeclare::new("jobs")
+                    .durable()
+                    .exclusive()
+                    .queue_type(QueueType::Quorum),
+                "quorum queues cannot be exclusive",
+            ),
+            (
+                QueueDeclare::new("jobs")
+                    .durable()
+                    .auto_delete()
+                    .queue_type(QueueType::Stream),
+                "stream queues cannot be auto-delete",
+            ),
+        ] {
+            assert_eq!(
+                declare.prepare(),
+                Err(Error::InvalidQueueArguments(message.into()))
+            );
+        }
+    }
+}
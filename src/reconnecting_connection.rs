@@ -0,0 +1,398 @@
+use crate::{
+    topology::{RestoredTopology, TopologyDefinition},
+    Channel, Connection, ConnectionProperties, Result,
+};
+use parking_lot::Mutex;
+use std::{
+    collections::hash_map::RandomState,
+    fmt,
+    future::Future,
+    hash::{BuildHasher, Hasher},
+    pin::Pin,
+    sync::Arc,
+    time::Duration,
+};
+use tracing::{info, warn};
+
+type BoxFuture<T> = Pin<Box<dyn Future<Output = T> + Send>>;
+type DialFn = Box<dyn Fn() -> BoxFuture<Result<Connection>> + Send + Sync>;
+type SleepFn = Box<dyn Fn(Duration) -> BoxFuture<()> + Send + Sync>;
+type ReconnectFn = Arc<dyn Fn(&Connection, &RestoredTopology) + Send + Sync>;
+
+/// Exponential backoff between two reconnection attempts.
+///
+/// The delay starts at `base`, doubles after each failed attempt up to `max`, and is then
+/// randomly reduced by up to `jitter` (a fraction of it) so that clients don't all reconnect at
+/// the same time.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Backoff {
+    base: Duration,
+    max: Duration,
+    jitter: f64,
+    max_attempts: Option<u32>,
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self {
+            base: Duration::from_millis(100),
+            max: Duration::from_secs(30),
+            jitter: 0.2,
+            max_attempts: None,
+        }
+    }
+}
+
+impl Backoff {
+    /// Start waiting for `base` after the first failed attempt, doubling the delay up to `max`.
+    ///
+    /// The jitter defaults to 0.2 and the attempts are unlimited, see [`Backoff::with_jitter`]
+    /// and [`Backoff::with_max_attempts`].
+    pub fn new(base: Duration, max: Duration) -> Self {
+        Self {
+            base,
+            max,
+            ..Default::default()
+        }
+    }
+
+    /// Randomly reduce each delay by up to this fraction of it, between 0 and 1.
+    #[must_use]
+    pub fn with_jitter(mut self, jitter: f64) -> Self {
+        self.jitter = jitter.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Give up after this many failed attempts, instead of retrying forever.
+    #[must_use]
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = Some(max_attempts);
+        self
+    }
+
+    /// The delay to wait for after the given failed attempt, starting at 0.
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let delay = self
+            .base
+            .checked_mul(2u32.saturating_pow(attempt))
+            .map_or(self.max, |delay| delay.min(self.max));
+        if self.jitter == 0.0 {
+            return delay;
+        }
+        // We don't need a proper random generator to spread the reconnections
+        let random = RandomState::new().build_hasher().finish() as f64 / u64::MAX as f64;
+        delay.mul_f64(1.0 - self.jitter * random)
+    }
+}
+
+/// A [`Connection`] which dials the server again when it gets closed or errored, and restores
+/// the topology which was declared on it.
+///
+/// The connection is checked each time it's accessed through
+/// [`ReconnectingConnection::ensure_connected`] or [`ReconnectingConnection::create_channel`].
+/// Reconnecting creates new channels and consumers: the ones obtained before are dead, the
+/// restored ones are handed to the [`ReconnectingConnection::on_reconnect`] callback. The
+/// operations which were in flight when the connection died fail, and are not replayed.
+///
+/// It can be shared between tasks: concurrent accesses to a dead connection only reconnect
+/// once.
+///
+/// [`Connection`]: ./struct.Connection.html
+pub struct ReconnectingConnection {
+    connection: Mutex<Arc<Connection>>,
+    reconnecting: async_lock::Mutex<()>,
+    dial: DialFn,
+    sleep: SleepFn,
+    backoff: Backoff,
+    on_reconnect: Mutex<Option<ReconnectFn>>,
+}
+
+impl ReconnectingConnection {
+    /// Connect to an AMQP server, see [`Connection::connect`].
+    ///
+    /// The first connection isn't retried, to report invalid parameters right away.
+    ///
+    /// [`Connection::connect`]: ./struct.Connection.html#method.connect
+    pub async fn connect(
+        uri: &str,
+        mut options: ConnectionProperties,
+        backoff: Backoff,
+    ) -> Result<Self> {
+        let reactor = options
+            .reactor
+            .get_or_insert_with(|| Arc::new(async_reactor_trait::AsyncIo))
+            .clone();
+        let uri = uri.to_owned();
+        let dial: DialFn = Box::new(move || {
+            let uri = uri.clone();
+            let options = options.clone();
+            Box::pin(async move { Connection::connect(&uri, options).await })
+        });
+        let sleep: SleepFn = Box::new(move |delay| {
+            let reactor = reactor.clone();
+            Box::pin(async move { reactor.sleep(delay).await })
+        });
+        let connection = dial().await?;
+        Ok(Self::new(connection, dial, sleep, backoff))
+    }
+
+    fn new(connection: Connection, dial: DialFn, sleep: SleepFn, backoff: Backoff) -> Self {
+        Self {
+            connection: Mutex::new(Arc::new(connection)),
+            reconnecting: async_lock::Mutex::new(()),
+            dial,
+            sleep,
+            backoff,
+            on_reconnect: Mutex::new(None),
+        }
+    }
+
+    /// Call `callback` with the new connection and the restored channels, queues and consumers
+    /// after each reconnection.
+    pub fn on_reconnect<F: Fn(&Connection, &RestoredTopology) + Send + Sync + 'static>(
+        &self,
+        callback: F,
+    ) {
+        *self.on_reconnect.lock() = Some(Arc::new(callback));
+    }
+
+    /// The current connection, which may be dead.
+    pub fn connection(&self) -> Arc<Connection> {
+        self.connection.lock().clone()
+    }
+
+    /// Whether the current connection is dead and has to be dialed again.
+    pub fn needs_reconnect(&self) -> bool {
+        let connection = self.connection();
+        let status = connection.status();
+        status.errored() || status.closed()
+    }
+
+    /// Get the current connection, reconnecting first if it's dead.
+    pub async fn ensure_connected(&self) -> Result<Arc<Connection>> {
+        if self.needs_reconnect() {
+            let _reconnecting = self.reconnecting.lock().await;
+            // Another task may have reconnected while we were waiting for it
+            if self.needs_reconnect() {
+                self.do_reconnect().await?;
+            }
+        }
+        Ok(self.connection())
+    }
+
+    /// Create a new channel, reconnecting first if the connection is dead.
+    pub async fn create_channel(&self) -> Result<Channel> {
+        self.ensure_connected().await?.create_channel().await
+    }
+
+    /// Dial the server again until it succeeds or the backoff gives up, then restore the
+    /// topology of the previous connection on the new one.
+    pub async fn reconnect(&self) -> Result<RestoredTopology> {
+        let _reconnecting = self.reconnecting.lock().await;
+        self.do_reconnect().await
+    }
+
+    async fn do_reconnect(&self) -> Result<RestoredTopology> {
+        let topology = self.connection().topology();
+        let mut attempt = 0;
+        loop {
+            let error = match self.try_reconnect(&topology).await {
+                Ok((connection, restored)) => {
+                    info!(attempt, "Reconnected");
+                    let connection = Arc::new(connection);
+                    *self.connection.lock() = connection.clone();
+                    let on_reconnect = self.on_reconnect.lock().clone();
+                    if let Some(on_reconnect) = on_reconnect {
+                        on_reconnect(&connection, &restored);
+                    }
+                    return Ok(restored);
+                }
+                Err(error) => error,
+            };
+            if self
+                .backoff
+                .max_attempts
+                .is_some_and(|max_attempts| attempt + 1 >= max_attempts)
+            {
+                return Err(error);
+            }
+            let delay = self.backoff.delay(attempt);
+            warn!(%error, attempt, ?delay, "Reconnection failed");
+            (self.sleep)(delay).await;
+            attempt += 1;
+        }
+    }
+
+    async fn try_reconnect(
+        &self,
+        topology: &TopologyDefinition,
+    ) -> Result<(Connection, RestoredTopology)> {
+        let connection = (self.dial)().await?;
+        // Restoring always opens a channel, don't do it if there is nothing to restore
+        let restored = if topology.exchanges.is_empty()
+            && topology.queues.is_empty()
+            && topology.channels.is_empty()
+        {
+            RestoredTopology::default()
+        } else {
+            connection.restore(topology.clone()).await?
+        };
+        Ok((connection, restored))
+    }
+}
+
+impl fmt::Debug for ReconnectingConnection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReconnectingConnection")
+            .field("connection", &*self.connection.lock())
+            .field("backoff", &self.backoff)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{connection::test_utils::connected_connection, ConnectionState, Error};
+    use futures_lite::future::block_on;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn connection(state: ConnectionState) -> Connection {
+        let (connection, _, _) = connected_connection();
+        connection.status().set_state(state);
+        connection
+    }
+
+    fn recording_sleep(delays: Arc<Mutex<Vec<Duration>>>) -> SleepFn {
+        Box::new(move |delay| {
+            delays.lock().push(delay);
+            Box::pin(async {})
+        })
+    }
+
+    #[test]
+    fn backoff_delays() {
+        let backoff = Backoff::new(Duration::from_millis(100), Duration::from_secs(1));
+        let backoff = backoff.with_jitter(0.0);
+        assert_eq!(
+            (0..6)
+                .map(|attempt| backoff.delay(attempt))
+                .collect::<Vec<_>>(),
+            [100, 200, 400, 800, 1000, 1000].map(Duration::from_millis)
+        );
+        assert_eq!(backoff.delay(u32::MAX), Duration::from_secs(1));
+
+        let backoff = backoff.with_jitter(0.5);
+        for _ in 0..100 {
+            let delay = backoff.delay(1);
+            assert!(delay > Duration::from_millis(100) && delay <= Duration::from_millis(200));
+        }
+    }
+
+    #[test]
+    fn reconnect_with_increasing_delays() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let dial_attempts = attempts.clone();
+        let dial: DialFn = Box::new(move || {
+            let attempt = dial_attempts.fetch_add(1, Ordering::SeqCst);
+            Box::pin(async move {
+                if attempt < 3 {
+                    Err(Error::InvalidConnectionState(ConnectionState::Closed))
+                } else {
+                    Ok(connection(ConnectionState::Connected))
+                }
+            })
+        });
+        let delays = Arc::new(Mutex::new(Vec::new()));
+        let backoff =
+            Backoff::new(Duration::from_millis(10), Duration::from_millis(30)).with_jitter(0.0);
+        let conn = ReconnectingConnection::new(
+            connection(ConnectionState::Connected),
+            dial,
+            recording_sleep(delays.clone()),
+            backoff,
+        );
+        let reconnected = Arc::new(AtomicUsize::new(0));
+        let on_reconnect = reconnected.clone();
+        conn.on_reconnect(move |connection, restored| {
+            assert!(connection.status().connected());
+            assert!(restored.channels.is_empty());
+            on_reconnect.fetch_add(1, Ordering::SeqCst);
+        });
+
+        // Nothing to do while connected
+        assert!(block_on(conn.ensure_connected()).is_ok());
+        assert_eq!(attempts.load(Ordering::SeqCst), 0);
+
+        // Force a disconnect
+        conn.connection().status().set_state(ConnectionState::Error);
+        assert!(conn.needs_reconnect());
+        assert!(block_on(conn.ensure_connected()).is_ok());
+        assert!(!conn.needs_reconnect());
+        assert_eq!(attempts.load(Ordering::SeqCst), 4);
+        assert_eq!(
+            *delays.lock(),
+            [10, 20, 30].map(Duration::from_millis).to_vec()
+        );
+        assert_eq!(reconnected.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn reconnect_gives_up() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let dial_attempts = attempts.clone();
+        let dial: DialFn = Box::new(move || {
+            dial_attempts.fetch_add(1, Ordering::SeqCst);
+            Box::pin(async { Err(Error::InvalidConnectionState(ConnectionState::Closed)) })
+        });
+        let delays = Arc::new(Mutex::new(Vec::new()));
+        let backoff = Backoff::new(Duration::from_millis(10), Duration::from_secs(1))
+            .with_jitter(0.0)
+            .with_max_attempts(3);
+        let conn = ReconnectingConnection::new(
+            connection(ConnectionState::Closed),
+            dial,
+            recording_sleep(delays.clone()),
+            backoff,
+        );
+
+        assert_eq!(
+            block_on(conn.create_channel()).err(),
+            Some(Error::InvalidConnectionState(ConnectionState::Closed))
+        );
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+        assert_eq!(*delays.lock(), [10, 20].map(Duration::from_millis).to_vec());
+    }
+
+    #[test]
+    fn concurrent_accesses_reconnect_once() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        use futures_lite::future::{yield_now, zip};
+
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let dial_attempts = attempts.clone();
+        let dial: DialFn = Box::new(move || {
+            dial_attempts.fetch_add(1, Ordering::SeqCst);
+            Box::pin(async {
+                // Let the other access run while we're dialing
+                yield_now().await;
+                Ok(connection(ConnectionState::Connected))
+            })
+        });
+        let conn = ReconnectingConnection::new(
+            connection(ConnectionState::Closed),
+            dial,
+            recording_sleep(Arc::default()),
+            Backoff::default(),
+        );
+
+        let (first, second) = block_on(zip(conn.ensure_connected(), conn.ensure_connected()));
+        assert!(Arc::ptr_eq(&first.unwrap(), &second.unwrap()));
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    }
+}
@@ -115,6 +115,45 @@ impl Registry {
         }
     }
 
+    pub(crate) fn is_queue_declared(&self, name: &str) -> bool {
+        self.0
+            .lock()
+            .queues
+            .get(name)
+            .is_some_and(QueueDefinitionInternal::is_declared)
+    }
+
+    pub(crate) fn is_queue_durable(&self, name: &str) -> bool {
+        self.0
+            .lock()
+            .queues
+            .get(name)
+            .and_then(|queue| queue.options)
+            .is_some_and(|options| options.durable)
+    }
+
+    pub(crate) fn exchange_kind(&self, name: &str) -> Option<ExchangeKind> {
+        self.0.lock().exchanges.get(name)?.kind.clone()
+    }
+
+    pub(crate) fn exchange_bindings(&self, destination: &str) -> Vec<BindingDefinition> {
+        self.0
+            .lock()
+            .exchanges
+            .get(destination)
+            .map(|exchange| exchange.bindings.clone())
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn queue_bindings(&self, name: &str) -> Vec<BindingDefinition> {
+        self.0
+            .lock()
+            .queues
+            .get(name)
+            .map(|queue| queue.bindings.clone())
+            .unwrap_or_default()
+    }
+
     pub(crate) fn deregister_queue(&self, name: &str) {
         self.0.lock().queues.remove(name);
     }
@@ -152,3 +191,159 @@ struct Inner {
     exchanges: HashMap<ShortString, ExchangeDefinition>,
     queues: HashMap<ShortString, QueueDefinitionInternal>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::connection::test_utils::*;
+    use crate::options::QueueBindOptions;
+    use crate::types::FieldTable;
+    use crate::ExchangeDeclare;
+    use amq_protocol::frame::AMQPFrame;
+    use amq_protocol::protocol::AMQPClass;
+
+    #[test]
+    fn queue_bindings() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        use crate::protocol::queue;
+
+        let (conn, frames) = running_connection();
+        let server = |frame: &AMQPFrame| {
+            passive_declare_reply(frame, |method| match method {
+                AMQPClass::Queue(queue::AMQPMethod::Bind(_)) => Some(AMQPClass::Queue(
+                    queue::AMQPMethod::BindOk(queue::BindOk {}),
+                )),
+                AMQPClass::Queue(queue::AMQPMethod::Unbind(_)) => Some(AMQPClass::Queue(
+                    queue::AMQPMethod::UnbindOk(queue::UnbindOk {}),
+                )),
+                _ => None,
+            })
+        };
+        let channel = run_against_server(&conn, &frames, conn.create_channel(), server).unwrap();
+        for routing_key in ["orders.created", "orders.paid", "orders.shipped"] {
+            run_against_server(
+                &conn,
+                &frames,
+                channel.queue_bind(
+                    "orders",
+                    "amq.topic",
+                    routing_key,
+                    QueueBindOptions::default(),
+                    FieldTable::default(),
+                ),
+                server,
+            )
+            .unwrap();
+        }
+        run_against_server(
+            &conn,
+            &frames,
+            channel.queue_unbind("orders", "amq.topic", "orders.paid", FieldTable::default()),
+            server,
+        )
+        .unwrap();
+
+        let bindings = conn.queue_bindings("orders");
+        assert_eq!(
+            bindings
+                .iter()
+                .map(|binding| (binding.source.as_str(), binding.routing_key.as_str()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("amq.topic", "orders.created"),
+                ("amq.topic", "orders.shipped")
+            ]
+        );
+        assert!(conn.queue_bindings("unknown").is_empty());
+    }
+
+    #[test]
+    fn exchange_bindings() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        use crate::options::{ExchangeBindOptions, ExchangeUnbindOptions};
+        use crate::protocol::exchange;
+
+        let (conn, frames) = running_connection();
+        let server = |frame: &AMQPFrame| {
+            passive_declare_reply(frame, |method| match method {
+                AMQPClass::Exchange(exchange::AMQPMethod::Bind(_)) => Some(AMQPClass::Exchange(
+                    exchange::AMQPMethod::BindOk(exchange::BindOk {}),
+                )),
+                AMQPClass::Exchange(exchange::AMQPMethod::Unbind(_)) => Some(AMQPClass::Exchange(
+                    exchange::AMQPMethod::UnbindOk(exchange::UnbindOk {}),
+                )),
+                _ => None,
+            })
+        };
+        let channel = run_against_server(&conn, &frames, conn.create_channel(), server).unwrap();
+        run_against_server(
+            &conn,
+            &frames,
+            channel.exchange_bind(
+                "audit",
+                "orders",
+                "orders.#",
+                ExchangeBindOptions::default(),
+                FieldTable::default(),
+            ),
+            server,
+        )
+        .unwrap();
+        let bindings = conn.exchange_bindings("audit");
+        assert_eq!(bindings.len(), 1);
+        assert_eq!(bindings[0].source.as_str(), "orders");
+        assert_eq!(bindings[0].routing_key.as_str(), "orders.#");
+        assert!(conn.exchange_bindings("orders").is_empty());
+
+        run_against_server(
+            &conn,
+            &frames,
+            channel.exchange_unbind(
+                "audit",
+                "orders",
+                "orders.#",
+                ExchangeUnbindOptions::default(),
+                FieldTable::default(),
+            ),
+            server,
+        )
+        .unwrap();
+        assert!(conn.exchange_bindings("audit").is_empty());
+    }
+
+    #[test]
+    fn known_exchange_type() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        use crate::protocol::exchange;
+
+        let (conn, frames) = running_connection();
+        let server = |frame: &AMQPFrame| {
+            passive_declare_reply(frame, |method| match method {
+                AMQPClass::Exchange(exchange::AMQPMethod::Declare(_)) => Some(AMQPClass::Exchange(
+                    exchange::AMQPMethod::DeclareOk(exchange::DeclareOk {}),
+                )),
+                _ => None,
+            })
+        };
+        let channel = run_against_server(&conn, &frames, conn.create_channel(), server).unwrap();
+        run_against_server(
+            &conn,
+            &frames,
+            channel.declare_exchange(ExchangeDeclare::new("events", ExchangeKind::Topic)),
+            server,
+        )
+        .unwrap();
+        assert!(
+            run_against_server(&conn, &frames, conn.exchange_exists("elsewhere"), server).unwrap()
+        );
+
+        assert_eq!(
+            conn.known_exchange_type("events"),
+            Some(ExchangeKind::Topic)
+        );
+        assert_eq!(conn.known_exchange_type("elsewhere"), None);
+    }
+}
@@ -6,12 +6,19 @@ use parking_lot::Mutex;
 use std::{collections::VecDeque, fmt, sync::Arc};
 use tracing::{trace, warn};
 
+type ReturnFn = Arc<Mutex<dyn FnMut(BasicReturnMessage) + Send + 'static>>;
+
 #[derive(Clone, Default)]
 pub(crate) struct ReturnedMessages {
     inner: Arc<Mutex<Inner>>,
+    handler: Arc<Mutex<Option<ReturnFn>>>,
 }
 
 impl ReturnedMessages {
+    pub(crate) fn set_handler<R: FnMut(BasicReturnMessage) + Send + 'static>(&self, handler: R) {
+        *self.handler.lock() = Some(Arc::new(Mutex::new(handler)));
+    }
+
     pub(crate) fn start_new_delivery(&self, message: BasicReturnMessage) {
         self.inner.lock().current_message = Some(message);
     }
@@ -22,9 +29,11 @@ impl ReturnedMessages {
         properties: BasicProperties,
         confirm_mode: bool,
     ) {
-        self.inner
+        let message = self
+            .inner
             .lock()
-            .handle_content_header_frame(size, properties, confirm_mode);
+            .handle_content_header_frame(size, properties);
+        self.new_delivery_complete(message, confirm_mode);
     }
 
     pub(crate) fn handle_body_frame(
@@ -33,9 +42,22 @@ impl ReturnedMessages {
         payload: Vec<u8>,
         confirm_mode: bool,
     ) {
-        self.inner
-            .lock()
-            .handle_body_frame(remaining_size, payload, confirm_mode);
+        let message = self.inner.lock().handle_body_frame(remaining_size, payload);
+        self.new_delivery_complete(message, confirm_mode);
+    }
+
+    // Don't call the handler while holding the inner lock, nor the one of the handler slot as
+    // the handler may replace itself
+    fn new_delivery_complete(&self, message: Option<BasicReturnMessage>, confirm_mode: bool) {
+        if let Some(message) = message {
+            warn!(?message, "Server returned us a message");
+            let handler = self.handler.lock().clone();
+            if let Some(handler) = handler {
+                (handler.lock())(message);
+            } else {
+                self.inner.lock().store(message, confirm_mode);
+            }
+        }
     }
 
     pub(crate) fn drain(&self) -> Vec<BasicReturnMessage> {
@@ -78,13 +100,14 @@ impl Inner {
         &mut self,
         size: PayloadSize,
         properties: BasicProperties,
-        confirm_mode: bool,
-    ) {
+    ) -> Option<BasicReturnMessage> {
         if let Some(message) = self.current_message.as_mut() {
             message.properties = properties;
         }
         if size == 0 {
-            self.new_delivery_complete(confirm_mode);
+            self.current_message.take()
+        } else {
+            None
         }
     }
 
@@ -92,24 +115,22 @@ impl Inner {
         &mut self,
         remaining_size: PayloadSize,
         payload: Vec<u8>,
-        confirm_mode: bool,
-    ) {
+    ) -> Option<BasicReturnMessage> {
         if let Some(message) = self.current_message.as_mut() {
             message.receive_content(payload);
         }
         if remaining_size == 0 {
-            self.new_delivery_complete(confirm_mode);
+            self.current_message.take()
+        } else {
+            None
         }
     }
 
-    fn new_delivery_complete(&mut self, confirm_mode: bool) {
-        if let Some(message) = self.current_message.take() {
-            warn!(?message, "Server returned us a message");
-            if confirm_mode {
-                self.waiting_messages.push_back(message);
-            } else {
-                self.non_confirm_messages.push(message);
-            }
+    fn store(&mut self, message: BasicReturnMessage, confirm_mode: bool) {
+        if confirm_mode {
+            self.waiting_messages.push_back(message);
+        } else {
+            self.non_confirm_messages.push(message);
         }
     }
 
@@ -162,3 +183,97 @@ impl Inner {
         messages
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::connection::test_utils::*;
+
+    use crate::BasicProperties;
+    use amq_protocol::frame::{AMQPContentHeader, AMQPFrame};
+    use amq_protocol::protocol::{basic, AMQPClass};
+
+    #[test]
+    fn handler_replaces_itself() {
+        let returned_messages = ReturnedMessages::default();
+        let handled = Arc::new(Mutex::new(Vec::new()));
+        let first_handled = handled.clone();
+        let first_returned_messages = returned_messages.clone();
+        returned_messages.set_handler(move |message: BasicReturnMessage| {
+            first_handled.lock().push(("first", message.reply_code));
+            let second_handled = first_handled.clone();
+            first_returned_messages.set_handler(move |message: BasicReturnMessage| {
+                second_handled.lock().push(("second", message.reply_code));
+            });
+        });
+
+        for reply_code in [312, 313] {
+            returned_messages.start_new_delivery(BasicReturnMessage::new(
+                "".into(),
+                "orders".into(),
+                reply_code,
+                "NO_ROUTE".into(),
+            ));
+            returned_messages.handle_content_header_frame(0, BasicProperties::default(), false);
+        }
+        assert_eq!(*handled.lock(), [("first", 312), ("second", 313)]);
+        assert!(returned_messages.drain().is_empty());
+    }
+
+    #[test]
+    fn returned_messages_go_to_the_return_handler() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        use crate::options::BasicPublishOptions;
+        use futures_lite::future::{block_on, poll_once};
+        use parking_lot::Mutex;
+
+        let (conn, channel, frames) = connected_channel_with_frames();
+        conn.configuration().set_frame_max(4096);
+        let returned = Arc::new(Mutex::new(Vec::new()));
+        let handler_returned = returned.clone();
+        channel.on_return(move |message| handler_returned.lock().push(message));
+
+        let _ = block_on(poll_once(channel.basic_publish(
+            "amq.direct",
+            "nowhere",
+            BasicPublishOptions {
+                mandatory: true,
+                ..Default::default()
+            },
+            b"lost",
+            BasicProperties::default(),
+        )));
+        while frames.pop(true).is_some() {}
+        for frame in [
+            AMQPFrame::Method(
+                channel.id(),
+                AMQPClass::Basic(basic::AMQPMethod::Return(basic::Return {
+                    reply_code: 312,
+                    reply_text: "NO_ROUTE".into(),
+                    exchange: "amq.direct".into(),
+                    routing_key: "nowhere".into(),
+                })),
+            ),
+            AMQPFrame::Header(
+                channel.id(),
+                60,
+                Box::new(AMQPContentHeader {
+                    class_id: 60,
+                    body_size: 4,
+                    properties: BasicProperties::default(),
+                }),
+            ),
+            AMQPFrame::Body(channel.id(), b"lost".to_vec()),
+        ] {
+            handle_frame(&conn, frame).unwrap();
+        }
+
+        let returned = returned.lock();
+        assert_eq!(returned.len(), 1);
+        assert_eq!(returned[0].reply_code, 312);
+        assert_eq!(returned[0].routing_key.as_str(), "nowhere");
+        assert_eq!(returned[0].data, b"lost");
+        assert_eq!(block_on(channel.wait_for_confirms()), Ok(Vec::new()));
+    }
+}
@@ -0,0 +1,232 @@
+use crate::{
+    channel_status::ChannelStatus,
+    id_sequence::IdSequence,
+    message::{Delivery, DeliveryResult},
+    options::{BasicConsumeOptions, BasicPublishOptions},
+    types::{FieldTable, ShortString},
+    BasicProperties, Channel, Error, Promise, PromiseResolver, Result,
+};
+use parking_lot::Mutex;
+use std::{collections::HashMap, fmt, sync::Arc};
+use tracing::warn;
+
+const DIRECT_REPLY_TO: &str = "amq.rabbitmq.reply-to";
+
+/// Client for request/reply RPC over AMQP.
+///
+/// It relies on RabbitMQ's [direct reply-to](https://www.rabbitmq.com/direct-reply-to.html):
+/// each request is published with `reply_to` set to `amq.rabbitmq.reply-to` and a unique
+/// `correlation_id`, and [`RpcClient::call`] resolves with the reply carrying the same
+/// `correlation_id`.
+///
+/// To bound how long to wait for a reply, wrap the call with [`Connection::timeout`].
+///
+/// [`Connection::timeout`]: ./struct.Connection.html#method.timeout
+#[derive(Clone)]
+pub struct RpcClient {
+    channel: Channel,
+    inner: Arc<Mutex<Inner>>,
+}
+
+impl RpcClient {
+    /// Create a new RpcClient, consuming the replies on the given channel.
+    pub async fn new(channel: Channel) -> Result<Self> {
+        let consumer = channel
+            .basic_consume(
+                DIRECT_REPLY_TO,
+                "",
+                BasicConsumeOptions {
+                    no_ack: true,
+                    ..Default::default()
+                },
+                FieldTable::default(),
+            )
+            .await?;
+        let inner = Arc::new(Mutex::new(Inner::new(channel.status().clone())));
+        let replies = inner.clone();
+        consumer.set_delegate(move |delivery: DeliveryResult| {
+            replies.lock().handle_reply(delivery);
+            async {}
+        });
+        Ok(Self { channel, inner })
+    }
+
+    /// Publish a request and wait for its reply.
+    pub async fn call(
+        &self,
+        exchange: &str,
+        routing_key: &str,
+        payload: &[u8],
+        properties: BasicProperties,
+    ) -> Result<Delivery> {
+        let (correlation_id, reply) = self.inner.lock().register()?;
+        let _guard = PendingCall(self.inner.clone(), correlation_id.clone());
+        self.channel
+            .basic_publish(
+                exchange,
+                routing_key,
+                BasicPublishOptions::default(),
+                payload,
+                properties
+                    .with_correlation_id(correlation_id)
+                    .with_reply_to(DIRECT_REPLY_TO.into()),
+            )
+            .await?;
+        reply.await
+    }
+}
+
+impl fmt::Debug for RpcClient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug = f.debug_struct("RpcClient");
+        debug.field("channel", &self.channel);
+        if let Some(inner) = self.inner.try_lock() {
+            debug.field("pending", &inner.pending.keys());
+        }
+        debug.finish()
+    }
+}
+
+// Forget about the call if it gets dropped before its reply arrives, e.g. on timeout
+struct PendingCall(Arc<Mutex<Inner>>, ShortString);
+
+impl Drop for PendingCall {
+    fn drop(&mut self) {
+        self.0.lock().pending.remove(&self.1);
+    }
+}
+
+struct Inner {
+    channel_status: ChannelStatus,
+    correlation_id: IdSequence<u64>,
+    pending: HashMap<ShortString, PromiseResolver<Delivery>>,
+    error: Option<Error>,
+}
+
+impl Inner {
+    fn new(channel_status: ChannelStatus) -> Self {
+        Self {
+            channel_status,
+            correlation_id: IdSequence::new(false),
+            pending: HashMap::default(),
+            error: None,
+        }
+    }
+
+    fn register(&mut self) -> Result<(ShortString, Promise<Delivery>)> {
+        if let Some(error) = self.error.as_ref() {
+            return Err(error.clone());
+        }
+        let correlation_id = ShortString::from(self.correlation_id.next().to_string());
+        let (promise, resolver) = Promise::new();
+        self.pending.insert(correlation_id.clone(), resolver);
+        Ok((correlation_id, promise))
+    }
+
+    fn handle_reply(&mut self, delivery: DeliveryResult) {
+        match delivery {
+            Ok(Some(delivery)) => {
+                let resolver = delivery
+                    .properties
+                    .correlation_id()
+                    .as_ref()
+                    .and_then(|correlation_id| self.pending.remove(correlation_id));
+                if let Some(resolver) = resolver {
+                    resolver.swear(Ok(delivery));
+                } else {
+                    warn!(correlation_id=?delivery.properties.correlation_id(), "Got a reply for an unknown request");
+                }
+            }
+            Ok(None) => self.fail(Error::InvalidChannelState(self.channel_status.state())),
+            Err(error) => self.fail(error),
+        }
+    }
+
+    fn fail(&mut self, error: Error) {
+        for (_, resolver) in self.pending.drain() {
+            resolver.swear(Err(error.clone()));
+        }
+        self.error = Some(error);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::connection::test_utils::*;
+    use crate::BasicProperties;
+    use amq_protocol::frame::{AMQPContentHeader, AMQPFrame};
+    use amq_protocol::protocol::{basic, AMQPClass};
+
+    #[test]
+    fn rpc_client_round_trip() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        use crate::RpcClient;
+
+        let (conn, channel, frames) = connected_channel_with_frames();
+        conn.configuration().set_frame_max(4096);
+        let mut published = None;
+        // Loopback server echoing requests with their correlation id to our direct reply-to consumer
+        let mut server = |frame: &AMQPFrame| match frame {
+            AMQPFrame::Method(id, AMQPClass::Basic(basic::AMQPMethod::Consume(consume))) => {
+                assert_eq!(consume.queue.as_str(), "amq.rabbitmq.reply-to");
+                vec![AMQPFrame::Method(
+                    *id,
+                    AMQPClass::Basic(basic::AMQPMethod::ConsumeOk(basic::ConsumeOk {
+                        consumer_tag: "amq.ctag-rpc".into(),
+                    })),
+                )]
+            }
+            AMQPFrame::Header(_, _, header) => {
+                assert_eq!(
+                    header.properties.reply_to(),
+                    &Some("amq.rabbitmq.reply-to".into())
+                );
+                published = Some(header.properties.clone());
+                Vec::new()
+            }
+            AMQPFrame::Body(id, payload) => {
+                let properties = BasicProperties::default().with_correlation_id(
+                    published.take().unwrap().correlation_id().clone().unwrap(),
+                );
+                vec![
+                    AMQPFrame::Method(
+                        *id,
+                        AMQPClass::Basic(basic::AMQPMethod::Deliver(basic::Deliver {
+                            consumer_tag: "amq.ctag-rpc".into(),
+                            delivery_tag: 1,
+                            redelivered: false,
+                            exchange: "".into(),
+                            routing_key: "amq.rabbitmq.reply-to.g1h2AA".into(),
+                        })),
+                    ),
+                    AMQPFrame::Header(
+                        *id,
+                        60,
+                        Box::new(AMQPContentHeader {
+                            class_id: 60,
+                            body_size: payload.len() as u64,
+                            properties,
+                        }),
+                    ),
+                    AMQPFrame::Body(*id, payload.clone()),
+                ]
+            }
+            _ => Vec::new(),
+        };
+
+        let client =
+            run_against_server(&conn, &frames, RpcClient::new(channel.clone()), &mut server)
+                .unwrap();
+        for request in [&b"ping"[..], &b"pong"[..]] {
+            let reply = run_against_server(
+                &conn,
+                &frames,
+                client.call("", "rpc", request, BasicProperties::default()),
+                &mut server,
+            )
+            .unwrap();
+            assert_eq!(reply.data, request);
+        }
+    }
+}
@@ -0,0 +1,8 @@
+/// Limit the number of unacknowledged deliveries the server will send us.
+///
+/// A `prefetch_count` of 0 means no limit. The prefetch size is always 0 (no limit) as
+/// RabbitMQ doesn't support anything else.
+///
+/// RabbitMQ reinterprets the `global` flag: when `false`, the limit applies separately to
+/// each consumer started afterwards on this channel; when `true`, it is shared between
+/// all the consumers of this channel.
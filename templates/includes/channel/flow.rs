@@ -0,0 +1,8 @@
+/// Ask the server to pause (`active: false`) or resume (`active: true`) the deliveries on this
+/// channel, the outcome being available through [`ChannelStatus::receive_flow`].
+///
+/// RabbitMQ doesn't support pausing deliveries this way: it answers `active: false` with a
+/// `NOT_IMPLEMENTED` connection error, closing the whole connection and not only this channel.
+/// Use [`Channel::basic_qos`] or [`Channel::basic_cancel`] to throttle or stop consumers there.
+///
+/// [`ChannelStatus::receive_flow`]: ./struct.ChannelStatus.html#method.receive_flow